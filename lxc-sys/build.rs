@@ -20,10 +20,26 @@ fn main() {
         .allowlist_function("lxc_get_version")
         .allowlist_function("lxc_get_global_config_item")
         .allowlist_function("lxc_log_init")
+        .allowlist_function("lxc_get_wait_states")
+        .allowlist_function("lxc_attach_run_command")
         .allowlist_type("lxc_container")
         .allowlist_type("lxc_log")
+        .allowlist_type("lxc_attach_command_t")
         .allowlist_var("LXC_ATTACH_TERMINAL")
         .allowlist_var("LXC_ATTACH_DEFAULT")
+        .allowlist_var("LXC_CREATE_QUIET")
+        .allowlist_var("LXC_CLONE_KEEPNAME")
+        .allowlist_var("LXC_CLONE_KEEPMACADDR")
+        .allowlist_var("LXC_CLONE_SNAPSHOT")
+        .allowlist_var("LXC_CLONE_KEEPBDEVTYPE")
+        .allowlist_var("LXC_CLONE_MAYBE_SNAPSHOT")
+        .allowlist_var("MIGRATE_PRE_DUMP")
+        .allowlist_var("MIGRATE_DUMP")
+        .allowlist_var("MIGRATE_RESTORE")
+        .allowlist_var("MIGRATE_FEATURE_CHECK")
+        .allowlist_var("FEATURE_MEM_TRACK")
+        .allowlist_var("FEATURE_LAZY_PAGES")
+        .allowlist_var("LXC_MOUNT_API_V1")
         // The input header we would like to generate
         // bindings for.
         .header("wrapper.h")
@@ -1,12 +1,21 @@
 // SPDX-License-Identifier: LGPL-2.1+
 
+use std::ffi::OsStr;
+use std::io::Write;
+use std::os::unix::ffi::OsStringExt;
+use std::os::unix::fs::{FileTypeExt, MetadataExt, OpenOptionsExt};
+use std::os::unix::io::AsRawFd;
 use std::os::unix::process::ExitStatusExt;
+use std::path::{Path, PathBuf};
 use std::process::{exit, ExitStatus};
+use std::time::Duration;
 
 use anyhow::{bail, Error};
 
 use rlxc::cli::rlxc as cli;
 use rlxc::lxc::{self, Lxc};
+use rlxc::util::ffi::ToCString;
+use rlxc::util::tty;
 #[macro_use]
 extern crate prettytable;
 use prettytable::Table;
@@ -19,6 +28,160 @@ fn may_control_container(c: &Lxc) -> Result<(), Error> {
     Ok(())
 }
 
+/// The lxcpath given by `--path`, or the default one.
+fn lxc_path<'a>(args: &'a clap::ArgMatches) -> Result<&'a OsStr, Error> {
+    let spath = args
+        .value_of_os("path")
+        .unwrap_or_else(|| lxc::get_default_path().as_ref());
+    if spath.is_empty() {
+        bail!("Missing required argument: 'path' and no default path set");
+    }
+    Ok(spath)
+}
+
+fn defined_container(args: &clap::ArgMatches) -> Result<Lxc, Error> {
+    let sname = args.value_of_os("name").unwrap();
+    let spath = lxc_path(args)?;
+
+    let container = Lxc::new(sname, spath)?;
+
+    may_control_container(&container)?;
+
+    if !container.is_defined() {
+        bail!("Container does not exist");
+    }
+
+    Ok(container)
+}
+
+fn running_container(args: &clap::ArgMatches) -> Result<Lxc, Error> {
+    let sname = args.value_of_os("name").unwrap();
+    let spath = lxc_path(args)?;
+
+    let container = Lxc::new(sname, spath)?;
+
+    may_control_container(&container)?;
+
+    if !container.is_running() {
+        bail!("Container not running");
+    }
+
+    Ok(container)
+}
+
+/// Parse a size with an optional `K`, `M`, `G` or `T` suffix into bytes.
+fn parse_size(value: &str) -> Result<u64, Error> {
+    let (num, shift) = match value.chars().last() {
+        Some('K') | Some('k') => (&value[..value.len() - 1], 10),
+        Some('M') | Some('m') => (&value[..value.len() - 1], 20),
+        Some('G') | Some('g') => (&value[..value.len() - 1], 30),
+        Some('T') | Some('t') => (&value[..value.len() - 1], 40),
+        _ => (value, 0),
+    };
+
+    match num.parse::<u64>() {
+        Ok(n) => match n.checked_mul(1 << shift) {
+            Some(size) => Ok(size),
+            None => bail!("Invalid size: {} is too large", value),
+        },
+        Err(e) => bail!("Invalid size: {:?}", e),
+    }
+}
+
+/// Parse the `timeout` argument where `-1` means to wait forever.
+fn parse_timeout(args: &clap::ArgMatches) -> Result<Option<Duration>, Error> {
+    match args.value_of("timeout") {
+        None => Ok(None),
+        Some(value) => match value.parse::<i32>() {
+            Ok(-1) => Ok(None),
+            Ok(n) => {
+                if n < 0 {
+                    bail!("Invalid timeout (must be -1, 0 or positive)");
+                }
+                Ok(Some(Duration::from_secs(n as u64)))
+            }
+            Err(e) => bail!("Invalid timeout: {:?}", e),
+        },
+    }
+}
+
+/// Parse an escape character given as `^x` or `x` into the corresponding
+/// control character.
+fn parse_escape(value: &str) -> Result<i32, Error> {
+    let key = value.strip_prefix('^').unwrap_or(value);
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_alphabetic() => {
+            Ok((c.to_ascii_lowercase() as i32) - ('a' as i32) + 1)
+        }
+        _ => bail!("Invalid escape character (must be ^a to ^z)"),
+    }
+}
+
+/// Parse a signal given as name, with or without `SIG` prefix, as realtime
+/// signal `RTMIN+n` or `RTMAX-n`, or as number. Numbers are left to the kernel
+/// to validate.
+fn parse_signal(value: &str) -> Result<i32, Error> {
+    if let Ok(n) = value.parse::<i32>() {
+        if n < 0 {
+            bail!("Invalid signal {}", value);
+        }
+        return Ok(n);
+    }
+
+    let name = value.to_ascii_uppercase();
+    let name = name.strip_prefix("SIG").unwrap_or(&name);
+    let realtime = |base: i32, offset: &str, sign: i32| match offset.parse() {
+        Ok(n) if (0..=libc::SIGRTMAX() - libc::SIGRTMIN()).contains(&n) => {
+            Ok(base + sign * n)
+        }
+        _ => bail!("Invalid signal {}", value),
+    };
+    if let Some(offset) = name.strip_prefix("RTMIN+") {
+        return realtime(libc::SIGRTMIN(), offset, 1);
+    }
+    if let Some(offset) = name.strip_prefix("RTMAX-") {
+        return realtime(libc::SIGRTMAX(), offset, -1);
+    }
+
+    Ok(match name {
+        "HUP" => libc::SIGHUP,
+        "INT" => libc::SIGINT,
+        "QUIT" => libc::SIGQUIT,
+        "ILL" => libc::SIGILL,
+        "TRAP" => libc::SIGTRAP,
+        "ABRT" | "IOT" => libc::SIGABRT,
+        "BUS" => libc::SIGBUS,
+        "FPE" => libc::SIGFPE,
+        "KILL" => libc::SIGKILL,
+        "USR1" => libc::SIGUSR1,
+        "SEGV" => libc::SIGSEGV,
+        "USR2" => libc::SIGUSR2,
+        "PIPE" => libc::SIGPIPE,
+        "ALRM" => libc::SIGALRM,
+        "TERM" => libc::SIGTERM,
+        "STKFLT" => libc::SIGSTKFLT,
+        "CHLD" => libc::SIGCHLD,
+        "CONT" => libc::SIGCONT,
+        "STOP" => libc::SIGSTOP,
+        "TSTP" => libc::SIGTSTP,
+        "TTIN" => libc::SIGTTIN,
+        "TTOU" => libc::SIGTTOU,
+        "URG" => libc::SIGURG,
+        "XCPU" => libc::SIGXCPU,
+        "XFSZ" => libc::SIGXFSZ,
+        "VTALRM" => libc::SIGVTALRM,
+        "PROF" => libc::SIGPROF,
+        "WINCH" => libc::SIGWINCH,
+        "IO" | "POLL" => libc::SIGIO,
+        "PWR" => libc::SIGPWR,
+        "SYS" => libc::SIGSYS,
+        "RTMIN" => libc::SIGRTMIN(),
+        "RTMAX" => libc::SIGRTMAX(),
+        _ => bail!("Invalid signal {}", value),
+    })
+}
+
 fn initialize_log(
     subcommand: &'static str,
     args: &clap::ArgMatches,
@@ -45,12 +208,7 @@ fn initialize_log(
 
 fn cmd_start(args: &clap::ArgMatches) -> Result<(), Error> {
     let sname = args.value_of_os("name").unwrap();
-    let spath = args
-        .value_of_os("path")
-        .unwrap_or_else(|| lxc::get_default_path().as_ref());
-    if spath.is_empty() {
-        bail!("Missing required argument: 'path' and no default path set");
-    }
+    let spath = lxc_path(args)?;
 
     let vals: Vec<_> = match args.values_of_os("command") {
         None => Vec::new(),
@@ -79,12 +237,7 @@ fn cmd_start(args: &clap::ArgMatches) -> Result<(), Error> {
 fn cmd_stop(args: &clap::ArgMatches) -> Result<(), Error> {
     let sname = args.value_of_os("name").unwrap_or_else(|| "".as_ref());
 
-    let spath = args
-        .value_of_os("path")
-        .unwrap_or_else(|| lxc::get_default_path().as_ref());
-    if spath.is_empty() {
-        bail!("Missing required argument: 'path' and no default path set");
-    }
+    let spath = lxc_path(args)?;
 
     let all = args.is_present("all");
 
@@ -93,19 +246,7 @@ fn cmd_stop(args: &clap::ArgMatches) -> Result<(), Error> {
     }
 
     let force = args.is_present("force");
-    let timeout = match args.value_of("timeout") {
-        None => None,
-        Some(value) => match value.parse::<i32>() {
-            Ok(-1) => None,
-            Ok(n) => {
-                if n < 0 {
-                    bail!("Invalid timeout (must be -1, 0 or positive)");
-                }
-                Some(std::time::Duration::from_secs(n as u64))
-            }
-            Err(e) => bail!("Invalid timeout: {:?}", e),
-        },
-    };
+    let timeout = parse_timeout(args)?;
 
     let stop_function = |name| {
         let container = Lxc::new(name, spath)?;
@@ -141,15 +282,22 @@ fn cmd_stop(args: &clap::ArgMatches) -> Result<(), Error> {
     Ok(())
 }
 
+fn cmd_kill(args: &clap::ArgMatches) -> Result<(), Error> {
+    let container = running_container(args)?;
+    let signal = parse_signal(args.value_of("signal").unwrap())?;
+
+    container.kill(signal)
+}
+
 fn cmd_exec(args: &clap::ArgMatches) -> i32 {
     let sname = args.value_of_os("name").unwrap();
-    let spath = args
-        .value_of_os("path")
-        .unwrap_or_else(|| lxc::get_default_path().as_ref());
-    if spath.is_empty() {
-        eprintln!("Missing required argument: 'path' and no default path set");
-        return 1;
-    }
+    let spath = match lxc_path(args) {
+        Ok(p) => p,
+        Err(err) => {
+            eprintln!("{}", err);
+            return 1;
+        }
+    };
 
     let vals: Vec<_> = args.values_of_os("command").unwrap().collect();
 
@@ -224,8 +372,48 @@ fn cmd_exec(args: &clap::ArgMatches) -> i32 {
     };
     options = options.gid(gid);
 
+    // Prefer a pty owned by the container, so programs like sudo accept it.
+    // Older liblxc versions and kernels can't provide one.
+    if tty::is_tty(0) {
+        if let Ok((ptx, pty)) = container.open_pty() {
+            return match exec_pty(&container, &mut options, ptx, pty, vals) {
+                Ok(code) => code,
+                Err(err) => {
+                    eprintln!("error: {}", err);
+                    1
+                }
+            };
+        }
+    }
+
     let ret = container.attach_run_wait(&mut options, vals[0], vals);
-    let status = ExitStatus::from_raw(ret);
+    exit_code(ExitStatus::from_raw(ret))
+}
+
+/// Run `argv` on the container pty `pty` and proxy it to our terminal.
+fn exec_pty(
+    container: &Lxc,
+    options: &mut lxc::AttachOptions,
+    ptx: std::fs::File,
+    pty: std::fs::File,
+    argv: Vec<&OsStr>,
+) -> Result<i32, Error> {
+    let pid = container.attach_pty(options, pty, argv[0], argv)?;
+
+    // There is nothing to detach from, the program has to exit.
+    let terminal = lxc::TerminalOptions::new().escape(-1);
+    let proxied = lxc::proxy_terminal(&ptx, &terminal);
+
+    let mut status = 0;
+    if unsafe { libc::waitpid(pid, &mut status, 0) } < 0 {
+        bail!("failed to wait: {}", std::io::Error::last_os_error());
+    }
+    proxied?;
+    Ok(exit_code(ExitStatus::from_raw(status)))
+}
+
+/// Map the exit status of a program to our exit code like shells do.
+fn exit_code(status: ExitStatus) -> i32 {
     if status.success() {
         return 0;
     }
@@ -242,12 +430,7 @@ fn cmd_exec(args: &clap::ArgMatches) -> i32 {
 fn cmd_freeze(args: &clap::ArgMatches) -> Result<(), Error> {
     let sname = args.value_of_os("name").unwrap_or_else(|| "".as_ref());
 
-    let spath = args
-        .value_of_os("path")
-        .unwrap_or_else(|| lxc::get_default_path().as_ref());
-    if spath.is_empty() {
-        bail!("Missing required argument: 'path' and no default path set");
-    }
+    let spath = lxc_path(args)?;
 
     let all = args.is_present("all");
 
@@ -288,12 +471,7 @@ fn cmd_freeze(args: &clap::ArgMatches) -> Result<(), Error> {
 fn cmd_unfreeze(args: &clap::ArgMatches) -> Result<(), Error> {
     let sname = args.value_of_os("name").unwrap_or_else(|| "".as_ref());
 
-    let spath = args
-        .value_of_os("path")
-        .unwrap_or_else(|| lxc::get_default_path().as_ref());
-    if spath.is_empty() {
-        bail!("Missing required argument: 'path' and no default path set");
-    }
+    let spath = lxc_path(args)?;
 
     let all = args.is_present("all");
 
@@ -332,12 +510,7 @@ fn cmd_unfreeze(args: &clap::ArgMatches) -> Result<(), Error> {
 }
 
 fn cmd_list(args: &clap::ArgMatches) -> Result<(), Error> {
-    let spath = args
-        .value_of_os("path")
-        .unwrap_or_else(|| lxc::get_default_path().as_ref());
-    if spath.is_empty() {
-        bail!("Missing required argument: 'path' and no default path set");
-    }
+    let spath = lxc_path(args)?;
 
     let mut table = Table::new();
     table.add_row(row!["NAME", "STATE", "IPV4", "IPV6"]);
@@ -374,23 +547,838 @@ fn cmd_list(args: &clap::ArgMatches) -> Result<(), Error> {
 }
 
 fn cmd_login(args: &clap::ArgMatches) -> Result<(), Error> {
+    let container = running_container(args)?;
+
+    let ttynum = match args.value_of("tty").map(str::parse::<i32>) {
+        None => 0,
+        Some(Ok(n)) if n >= -1 => n,
+        Some(_) => bail!("Invalid tty (must be -1, 0 or positive)"),
+    };
+    let escape = match args.value_of("escape") {
+        Some(escape) => parse_escape(escape)?,
+        None => 1,
+    };
+    // The proxy works on the tty allocated here, so only the escape character
+    // is needed.
+    let options = lxc::TerminalOptions::new().escape(escape);
+
+    let console = container.console_getfd(ttynum)?;
+    let escape = (escape as u8 + b'a' - 1) as char;
+    eprint!(
+        "\nConnected to tty {0}\nType <Ctrl+{1} q> to exit the console, \
+         <Ctrl+{1} ?> for more options\n\n",
+        console.ttynum(),
+        escape
+    );
+    console.proxy(&options)
+}
+
+fn cmd_create(args: &clap::ArgMatches) -> Result<(), Error> {
     let sname = args.value_of_os("name").unwrap();
-    let spath = args
-        .value_of_os("path")
-        .unwrap_or_else(|| lxc::get_default_path().as_ref());
-    if spath.is_empty() {
-        bail!("Missing required argument: 'path' and no default path set");
+    let spath = lxc_path(args)?;
+
+    let template = args.value_of_os("template").unwrap();
+    let bdevtype = args.value_of("bdev");
+
+    let vals: Vec<_> = match args.values_of_os("template-args") {
+        None => Vec::new(),
+        Some(v) => v.collect(),
+    };
+
+    let mut specs = lxc::BdevSpecs::new();
+    if let Some(fstype) = args.value_of_os("fstype") {
+        specs = specs.set_fstype(fstype)?;
+    }
+    if let Some(fssize) = args.value_of("fssize") {
+        specs = specs.set_fssize(parse_size(fssize)?);
+    }
+    if let Some(dir) = args.value_of_os("dir") {
+        specs = specs.set_dir(dir)?;
+    }
+    if let Some(zfsroot) = args.value_of_os("zfsroot") {
+        specs = specs.set_zfsroot(zfsroot)?;
+    }
+    if let Some(vg) = args.value_of_os("vgname") {
+        specs = specs.set_lvm_vg(vg)?;
+    }
+    if let Some(lv) = args.value_of_os("lvname") {
+        specs = specs.set_lvm_lv(lv)?;
+    }
+    if let Some(thinpool) = args.value_of_os("thinpool") {
+        specs = specs.set_lvm_thinpool(thinpool)?;
     }
 
     let container = Lxc::new(sname, spath)?;
 
     may_control_container(&container)?;
 
+    if container.is_defined() {
+        bail!("Container already exists");
+    }
+
+    container.create(
+        template,
+        bdevtype,
+        Some(&mut specs),
+        args.is_present("quiet"),
+        vals,
+    )
+}
+
+fn cmd_destroy(args: &clap::ArgMatches) -> Result<(), Error> {
+    let container = defined_container(args)?;
+
+    if container.is_running() {
+        if !args.is_present("force") {
+            bail!("Container is running, use --force to stop it first");
+        }
+        container.stop()?;
+    }
+
+    if args.is_present("snapshots") {
+        return container.destroy_with_snapshots();
+    }
+
+    container.destroy()
+}
+
+fn cmd_copy(args: &clap::ArgMatches) -> Result<(), Error> {
+    let snewname = args.value_of_os("newname").unwrap();
+
+    let mut options = lxc::CloneOptions::new()
+        .snapshot(args.is_present("snapshot"))
+        .maybe_snapshot(args.is_present("maybe-snapshot"))
+        .keep_name(args.is_present("keepname"))
+        .keep_macaddr(args.is_present("keepmac"))
+        .keep_bdevtype(args.is_present("keepbdevtype"));
+    if let Some(newpath) = args.value_of_os("newpath") {
+        options = options.set_lxcpath(newpath)?;
+    }
+    if let Some(bdev) = args.value_of_os("bdev") {
+        options = options.set_bdevtype(bdev)?;
+    }
+    if let Some(fssize) = args.value_of("fssize") {
+        options = options.set_newsize(parse_size(fssize)?);
+    }
+
+    let container = defined_container(args)?;
+
+    container.copy(snewname, &options)?;
+    Ok(())
+}
+
+fn cmd_snapshot_create(args: &clap::ArgMatches) -> Result<(), Error> {
+    let container = defined_container(args)?;
+    let comment = args.value_of_os("comment").map(Path::new);
+
+    let snapname = container.snapshot(comment)?;
+    println!("Created snapshot {}", snapname);
+    Ok(())
+}
+
+fn cmd_snapshot_list(args: &clap::ArgMatches) -> Result<(), Error> {
+    let container = defined_container(args)?;
+
+    let mut table = Table::new();
+    table.add_row(row!["NAME", "TIMESTAMP", "COMMENT"]);
+    for snapshot in container.snapshot_list()? {
+        table.add_row(row![
+            &snapshot.name,
+            &snapshot.timestamp,
+            snapshot.comment().unwrap_or_default()
+        ]);
+    }
+    table.printstd();
+    Ok(())
+}
+
+fn cmd_snapshot_restore(args: &clap::ArgMatches) -> Result<(), Error> {
+    let container = defined_container(args)?;
+    let snapname = args.value_of("snapshot").unwrap();
+
+    container.snapshot_restore(snapname, args.value_of("newname"))
+}
+
+fn cmd_snapshot_delete(args: &clap::ArgMatches) -> Result<(), Error> {
+    let container = defined_container(args)?;
+
+    if args.is_present("all") {
+        return container.snapshot_destroy_all();
+    }
+
+    container.snapshot_destroy(args.value_of("snapshot").unwrap())
+}
+
+fn cmd_config_get(args: &clap::ArgMatches) -> Result<(), Error> {
+    let container = defined_container(args)?;
+    let key = args.value_of("key").unwrap();
+
+    println!("{}", container.config().get(key)?);
+    Ok(())
+}
+
+fn cmd_config_set(args: &clap::ArgMatches) -> Result<(), Error> {
+    let container = defined_container(args)?;
+    let key = args.value_of("key").unwrap();
+    let value = args.value_of("value").unwrap();
+
+    let config = container.config();
+    config.set(key, value)?;
+    config.save(None)
+}
+
+fn cmd_config_unset(args: &clap::ArgMatches) -> Result<(), Error> {
+    let container = defined_container(args)?;
+    let key = args.value_of("key").unwrap();
+
+    let config = container.config();
+    config.clear(key)?;
+    config.save(None)
+}
+
+fn cmd_config_show(args: &clap::ArgMatches) -> Result<(), Error> {
+    let container = defined_container(args)?;
+
+    let config = container.config();
+    for key in config.keys(args.value_of("prefix"))? {
+        // Not every key liblxc knows about can be queried on its own.
+        let value = match config.get(&key) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+        for line in value.lines().filter(|l| !l.is_empty()) {
+            println!("{} = {}", key, line);
+        }
+    }
+    Ok(())
+}
+
+fn cmd_config_diff(args: &clap::ArgMatches) -> Result<(), Error> {
+    let container = defined_container(args)?;
+
     if !container.is_running() {
         bail!("Container not running");
     }
 
-    container.terminal()
+    let config = container.config();
+    let mut table = Table::new();
+    table.add_row(row!["KEY", "RUNNING", "SAVED"]);
+    for key in config.keys(None)? {
+        // Not every key liblxc knows about can be queried on its own.
+        let saved = match config.get(&key) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+        let running = match config.get_running(&key) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+
+        if running != saved {
+            table.add_row(row![&key, running, saved]);
+        }
+    }
+    table.printstd();
+    Ok(())
+}
+
+fn cmd_cgroup_get(args: &clap::ArgMatches) -> Result<(), Error> {
+    let container = running_container(args)?;
+    let key = args.value_of("key").unwrap();
+
+    println!("{}", container.get_cgroup_item(key)?.trim_end());
+    Ok(())
+}
+
+fn cmd_cgroup_set(args: &clap::ArgMatches) -> Result<(), Error> {
+    let container = running_container(args)?;
+    let key = args.value_of("key").unwrap();
+    let value = args.value_of("value").unwrap();
+
+    container.set_cgroup_item(key, value)
+}
+
+fn cmd_checkpoint(args: &clap::ArgMatches) -> Result<(), Error> {
+    let container = running_container(args)?;
+
+    let mut options = lxc::MigrateOptions::new()
+        .set_directory(args.value_of_os("directory").unwrap())?
+        .set_stop(args.is_present("stop"))
+        .set_verbose(args.is_present("verbose"));
+    if let Some(dir) = args.value_of_os("predump-dir") {
+        options = options.set_predump_dir(dir)?;
+    }
+    if let Some(script) = args.value_of_os("action-script") {
+        options = options.set_action_script(script)?;
+    }
+    if let Some(limit) = args.value_of("ghost-limit") {
+        options = options.set_ghost_limit(parse_size(limit)?);
+    }
+
+    if args.is_present("pre-dump") {
+        return container.migrate(lxc::MigrateCmd::PreDump, &mut options);
+    }
+
+    container.migrate(lxc::MigrateCmd::Dump, &mut options)
+}
+
+fn cmd_restore(args: &clap::ArgMatches) -> Result<(), Error> {
+    let container = defined_container(args)?;
+
+    if container.is_running() {
+        bail!("Container already running");
+    }
+
+    let mut options = lxc::MigrateOptions::new()
+        .set_directory(args.value_of_os("directory").unwrap())?
+        .set_verbose(args.is_present("verbose"));
+
+    container.migrate(lxc::MigrateCmd::Restore, &mut options)
+}
+
+/// Sum up the size of the memory pages stored in a checkpoint directory.
+fn checkpoint_pages_size(dir: &Path) -> Result<u64, Error> {
+    let mut size = 0;
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with("pages-") && name.ends_with(".img") {
+            size += entry.metadata()?.len();
+        }
+    }
+    Ok(size)
+}
+
+fn cmd_migrate(args: &clap::ArgMatches) -> Result<(), Error> {
+    let sname = args.value_of_os("name").unwrap();
+    let to_path = args.value_of_os("to-path").unwrap();
+    let workdir = Path::new(args.value_of_os("directory").unwrap());
+    let verbose = args.is_present("verbose");
+
+    let max_iterations = match args.value_of("max-iterations") {
+        None => 10,
+        Some(value) => match value.parse::<u32>() {
+            Ok(n) => n,
+            Err(e) => bail!("Invalid number of iterations: {:?}", e),
+        },
+    };
+    let threshold = parse_size(args.value_of("threshold").unwrap_or("1M"))?;
+
+    let container = running_container(args)?;
+
+    if Lxc::new(sname, to_path)?.is_defined() {
+        bail!("Container already exists in {:?}", to_path);
+    }
+
+    // Pre-dumps require CRIU's memory tracking, without it we can only do a
+    // single final dump.
+    let mut options = lxc::MigrateOptions::new().mem_track(true);
+    let mem_track = container
+        .migrate(lxc::MigrateCmd::FeatureCheck, &mut options)
+        .is_ok();
+    let iterations = if mem_track { max_iterations } else { 0 };
+
+    // Each pre-dump only contains the pages dirtied since the previous one.
+    // Keep going until that delta is small enough or stops shrinking.
+    let mut predump: Option<String> = None;
+    let mut last_size: Option<u64> = None;
+    for i in 0..iterations {
+        let name = format!("predump{}", i);
+        let dir = workdir.join(&name);
+        std::fs::create_dir_all(&dir)?;
+
+        let mut options = lxc::MigrateOptions::new()
+            .set_directory(&dir)?
+            .set_verbose(verbose);
+        if let Some(prev) = &predump {
+            options = options.set_predump_dir(format!("../{}", prev))?;
+        }
+        container.migrate(lxc::MigrateCmd::PreDump, &mut options)?;
+        predump = Some(name);
+
+        let size = checkpoint_pages_size(&dir)?;
+        println!("Pre-dump {}: {} bytes of memory changed", i, size);
+        let converged = size <= threshold
+            || matches!(last_size, Some(last) if size >= last);
+        if converged {
+            break;
+        }
+        last_size = Some(size);
+    }
+
+    let dumpdir = workdir.join("dump");
+    std::fs::create_dir_all(&dumpdir)?;
+    let mut options = lxc::MigrateOptions::new()
+        .set_directory(&dumpdir)?
+        .set_stop(true)
+        .set_verbose(verbose);
+    if let Some(prev) = &predump {
+        options = options.set_predump_dir(format!("../{}", prev))?;
+    }
+    container.migrate(lxc::MigrateCmd::Dump, &mut options)?;
+
+    // The container is stopped now. Should anything fail from here on bring
+    // it back up where it was.
+    let mut restore = lxc::MigrateOptions::new()
+        .set_directory(&dumpdir)?
+        .set_verbose(verbose);
+    let clone = lxc::CloneOptions::new()
+        .keep_name(true)
+        .keep_macaddr(true)
+        .set_lxcpath(to_path)?;
+    // Keep the reason the migration failed even if the restart fails too.
+    let rollback = |err: Error, restore: &mut lxc::MigrateOptions| -> Error {
+        match container.migrate(lxc::MigrateCmd::Restore, restore) {
+            Ok(()) => err,
+            Err(restart) => {
+                let msg = format!(
+                    "{} (restarting the source container failed too: {})",
+                    err, restart
+                );
+                err.context(msg)
+            }
+        }
+    };
+    // liblxc refuses to copy a container that is still shutting down.
+    let moved = container
+        .wait(lxc::State::Stopped, Some(Duration::from_secs(30)))
+        .and_then(|()| container.copy(sname, &clone));
+    let moved = match moved {
+        Ok(c) => c,
+        Err(err) => return Err(rollback(err, &mut restore)),
+    };
+    if let Err(err) = moved.migrate(lxc::MigrateCmd::Restore, &mut restore) {
+        if let Err(cleanup) = moved.destroy() {
+            eprintln!(
+                "warning: failed to remove the copy in {:?}: {}",
+                to_path, cleanup
+            );
+        }
+        return Err(rollback(err, &mut restore));
+    }
+
+    // The container runs at the destination, so this is no longer fatal.
+    if let Err(err) = container.destroy() {
+        eprintln!("warning: failed to remove the source container: {}", err);
+    }
+    Ok(())
+}
+
+fn cmd_wait(args: &clap::ArgMatches) -> Result<(), Error> {
+    let states = lxc::get_wait_states();
+    let state: lxc::State = args.value_of("state").unwrap().parse()?;
+    if !states.contains(&state) {
+        let names: Vec<_> = states.iter().map(|s| s.as_str()).collect();
+        bail!("Invalid state: must be one of {}", names.join(", "));
+    }
+
+    let timeout = parse_timeout(args)?;
+
+    let container = defined_container(args)?;
+
+    container.wait(state, timeout)
+}
+
+fn cmd_restart(args: &clap::ArgMatches) -> Result<(), Error> {
+    // Like lxc-stop, don't wait forever for a clean reboot by default.
+    let timeout = if args.is_present("timeout") {
+        parse_timeout(args)?
+    } else {
+        Some(Duration::from_secs(60))
+    };
+    let container = running_container(args)?;
+
+    if !args.is_present("force") {
+        match container.reboot_with_timeout(timeout) {
+            Ok(()) => return Ok(()),
+            Err(err) => eprintln!("{}, stopping the container instead", err),
+        }
+    }
+
+    if container.is_running() {
+        container.stop()?;
+    }
+    container.start(false, Vec::new())
+}
+
+fn cmd_rename(args: &clap::ArgMatches) -> Result<(), Error> {
+    let container = defined_container(args)?;
+    let newname = args.value_of("newname").unwrap();
+
+    container.rename(newname)
+}
+
+/// Build the `lxc.mount.entry` and device cgroup configuration items which
+/// make the device node `src` available at `dest` in the container.
+fn device_config(
+    src: &Path,
+    dest: &Path,
+) -> Result<[(&'static str, String); 2], Error> {
+    let meta = std::fs::metadata(src)?;
+    let kind = if meta.file_type().is_char_device() {
+        'c'
+    } else if meta.file_type().is_block_device() {
+        'b'
+    } else {
+        bail!("{:?} is not a device node", src);
+    };
+
+    let (major, minor) = (libc::major(meta.rdev()), libc::minor(meta.rdev()));
+
+    let cgroup_key = if Path::new("/sys/fs/cgroup/cgroup.controllers").exists()
+    {
+        "lxc.cgroup2.devices.allow"
+    } else {
+        "lxc.cgroup.devices.allow"
+    };
+
+    // Mount entries are relative to the container's rootfs.
+    let dest = dest.strip_prefix("/").unwrap_or(dest);
+    Ok([
+        (
+            "lxc.mount.entry",
+            format!(
+                "{} {} none bind,optional,create=file 0 0",
+                src.display(),
+                dest.display()
+            ),
+        ),
+        (cgroup_key, format!("{} {}:{} rwm", kind, major, minor)),
+    ])
+}
+
+fn cmd_device_add(args: &clap::ArgMatches) -> Result<(), Error> {
+    let container = running_container(args)?;
+    let src = Path::new(args.value_of_os("src_path").unwrap());
+    let dest = args.value_of_os("dest_path").map(Path::new);
+
+    container.add_device_node(src, dest)?;
+
+    if args.is_present("persist") {
+        let config = container.config();
+        for (key, value) in device_config(src, dest.unwrap_or(src))?.iter() {
+            config.set(key, value)?;
+        }
+        config.save(None)?;
+    }
+    Ok(())
+}
+
+fn cmd_device_remove(args: &clap::ArgMatches) -> Result<(), Error> {
+    let container = running_container(args)?;
+    let src = Path::new(args.value_of_os("src_path").unwrap());
+    let dest = args.value_of_os("dest_path").map(Path::new);
+
+    container.remove_device_node(src, dest)?;
+
+    if args.is_present("persist") {
+        let config = container.config();
+        for (key, value) in device_config(src, dest.unwrap_or(src))?.iter() {
+            config.remove(key, value)?;
+        }
+        config.save(None)?;
+    }
+    Ok(())
+}
+
+fn cmd_network_attach(args: &clap::ArgMatches) -> Result<(), Error> {
+    let container = running_container(args)?;
+    let host_ifname = args.value_of("host_ifname").unwrap();
+
+    container.attach_interface(host_ifname, args.value_of("container_ifname"))
+}
+
+fn cmd_network_detach(args: &clap::ArgMatches) -> Result<(), Error> {
+    let container = running_container(args)?;
+    let host_ifname = args.value_of("host_ifname").unwrap();
+    let container_ifname =
+        args.value_of("container_ifname").unwrap_or(host_ifname);
+
+    container.detach_interface(container_ifname, Some(host_ifname))
+}
+
+/// A temporary read-only bind mount which is removed again on drop. Bind
+/// mounts ignore `MS_RDONLY` when they are created, so this is needed to hand
+/// a read-only mount to a container. The mount lives in a private directory
+/// below `/run` so other users can't interfere with it. Submounts of the
+/// source are not included as they would stay writable.
+struct ReadonlyBind {
+    dir: PathBuf,
+    path: PathBuf,
+}
+
+impl ReadonlyBind {
+    fn new(src: &Path) -> Result<Self, Error> {
+        // mkdtemp() creates the directory with mode 0700.
+        let mut template = b"/run/rlxc-mount-XXXXXX\0".to_vec();
+        if unsafe { libc::mkdtemp(template.as_mut_ptr() as *mut _) }.is_null() {
+            bail!(
+                "failed to create temporary directory in /run (needs root): {}",
+                std::io::Error::last_os_error()
+            );
+        }
+        template.pop();
+        let dir = PathBuf::from(std::ffi::OsString::from_vec(template));
+        let path = dir.join("mnt");
+
+        // From here on drop() cleans up after us.
+        let bind = ReadonlyBind { dir, path };
+        if src.is_dir() {
+            std::fs::create_dir(&bind.path)?;
+        } else {
+            std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .custom_flags(libc::O_NOFOLLOW)
+                .open(&bind.path)?;
+        }
+        bind.mount(Some(src), libc::MS_BIND)?;
+        bind.mount(None, libc::MS_BIND | libc::MS_REMOUNT | libc::MS_RDONLY)?;
+        Ok(bind)
+    }
+
+    fn mount(
+        &self,
+        src: Option<&Path>,
+        flags: libc::c_ulong,
+    ) -> Result<(), Error> {
+        let csrc = match src {
+            Some(p) => Some(p.to_c_string()?),
+            None => None,
+        };
+        let cpath = self.path.to_c_string()?;
+        let ret = unsafe {
+            libc::mount(
+                csrc.as_ref().map_or(std::ptr::null(), |s| s.as_ptr()),
+                cpath.as_ptr(),
+                std::ptr::null(),
+                flags,
+                std::ptr::null(),
+            )
+        };
+        if ret < 0 {
+            bail!(
+                "failed to create read-only bind mount: {}",
+                std::io::Error::last_os_error()
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Drop for ReadonlyBind {
+    fn drop(&mut self) {
+        if let Ok(cpath) = self.path.to_c_string() {
+            unsafe { libc::umount2(cpath.as_ptr(), libc::MNT_DETACH) };
+        }
+        if self.path.is_dir() {
+            let _ = std::fs::remove_dir(&self.path);
+        } else {
+            let _ = std::fs::remove_file(&self.path);
+        }
+        let _ = std::fs::remove_dir(&self.dir);
+    }
+}
+
+fn cmd_mount(args: &clap::ArgMatches) -> Result<(), Error> {
+    let container = running_container(args)?;
+    let host_path = Path::new(args.value_of_os("host_path").unwrap());
+    let container_path = Path::new(args.value_of_os("container_path").unwrap());
+
+    // liblxc hands mounts to the container through the shmounts directory.
+    let auto = container
+        .config()
+        .get_running("lxc.mount.auto")
+        .unwrap_or_default();
+    if !auto.split_whitespace().any(|a| a.starts_with("shmounts:")) {
+        bail!("Container has no shmounts configured (see lxc.mount.auto in lxc.container.conf(5))");
+    }
+
+    if !args.is_present("readonly") {
+        return container.mount(
+            host_path,
+            container_path,
+            None,
+            libc::MS_BIND | libc::MS_REC,
+        );
+    }
+
+    // A bind mount of a read-only mount stays read-only.
+    let bind = ReadonlyBind::new(host_path)?;
+    container.mount(&bind.path, container_path, None, libc::MS_BIND)
+}
+
+fn cmd_console_log(args: &clap::ArgMatches) -> Result<(), Error> {
+    let container = running_container(args)?;
+    let follow = args.is_present("follow");
+    let clear = args.is_present("clear");
+
+    let stdout = std::io::stdout();
+    let mut stdout = stdout.lock();
+    loop {
+        // liblxc advances the read position, so this only returns new output.
+        let data = container.console_log(true, clear, None)?;
+        stdout.write_all(&data)?;
+        stdout.flush()?;
+
+        if !follow || !container.is_running() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(200));
+    }
+    Ok(())
+}
+
+/// Parse a device given as `<c|b>:<major>:<minor>` into its file type and
+/// device numbers.
+fn parse_device(value: &str) -> Result<(u32, u32, u32), Error> {
+    let parts: Vec<&str> = value.split(':').collect();
+    if parts.len() != 3 {
+        bail!("Invalid device {} (must be <c|b>:<major>:<minor>)", value);
+    }
+    let kind = match parts[0] {
+        "c" => libc::S_IFCHR,
+        "b" => libc::S_IFBLK,
+        _ => bail!("Invalid device type {} (must be c or b)", parts[0]),
+    };
+    match (parts[1].parse(), parts[2].parse()) {
+        (Ok(major), Ok(minor)) => Ok((kind, major, minor)),
+        _ => bail!("Invalid device number in {}", value),
+    }
+}
+
+/// An intercepted `mknod()` or `mknodat()` call of the native architecture.
+struct MknodCall {
+    dirfd: i32,
+    path: u64,
+    mode: u32,
+    dev: u64,
+}
+
+impl MknodCall {
+    fn new(request: &lxc::SeccompRequest) -> Self {
+        let args = request.args();
+        #[cfg(target_arch = "x86_64")]
+        {
+            if request.syscall() == libc::SYS_mknod {
+                return Self {
+                    dirfd: libc::AT_FDCWD,
+                    path: args[0],
+                    mode: args[1] as u32,
+                    dev: args[2],
+                };
+            }
+        }
+        Self {
+            dirfd: args[0] as i32,
+            path: args[1],
+            mode: args[2] as u32,
+            dev: args[3],
+        }
+    }
+
+    /// Create the device node on behalf of the calling process of `request`,
+    /// resolving the path the same way it would have.
+    fn emulate(
+        &self,
+        request: &lxc::SeccompRequest,
+    ) -> Result<lxc::SeccompResponse, Error> {
+        let path = request.read_string(self.path, libc::PATH_MAX as usize)?;
+        let pid = request.pid();
+        let root = std::fs::File::open(format!("/proc/{}/root", pid))?;
+        let dir = if self.dirfd == libc::AT_FDCWD {
+            std::fs::File::open(format!("/proc/{}/cwd", pid))?
+        } else {
+            std::fs::File::open(format!("/proc/{}/fd/{}", pid, self.dirfd))?
+        };
+        let owner = std::fs::metadata(format!("/proc/{}", pid))?;
+        let status = std::fs::read_to_string(format!("/proc/{}/status", pid))?;
+        let umask = status
+            .lines()
+            .find_map(|l| l.strip_prefix("Umask:"))
+            .and_then(|m| u32::from_str_radix(m.trim(), 8).ok())
+            .unwrap_or(0o022);
+        if !request.is_valid() {
+            bail!("seccomp notification is no longer valid");
+        }
+
+        let mode = self.mode & !umask;
+        let pid = unsafe { libc::fork() };
+        if pid < 0 {
+            bail!("failed to fork: {}", std::io::Error::last_os_error());
+        }
+        if pid == 0 {
+            // Absolute paths and symlinks have to resolve within the
+            // container's root.
+            let ret = unsafe {
+                if libc::fchdir(root.as_raw_fd()) < 0
+                    || libc::chroot(b".\0".as_ptr() as *const _) < 0
+                    || libc::mknodat(
+                        dir.as_raw_fd(),
+                        path.as_ptr(),
+                        mode,
+                        self.dev,
+                    ) < 0
+                    || libc::fchownat(
+                        dir.as_raw_fd(),
+                        path.as_ptr(),
+                        owner.uid(),
+                        owner.gid(),
+                        libc::AT_SYMLINK_NOFOLLOW,
+                    ) < 0
+                {
+                    *libc::__errno_location()
+                } else {
+                    0
+                }
+            };
+            unsafe { libc::_exit(ret) };
+        }
+
+        let mut status = 0;
+        if unsafe { libc::waitpid(pid, &mut status, 0) } < 0 {
+            bail!("failed to wait: {}", std::io::Error::last_os_error());
+        }
+        match ExitStatus::from_raw(status).code() {
+            Some(0) => Ok(lxc::SeccompResponse::Value(0)),
+            Some(errno) => Ok(lxc::SeccompResponse::Errno(errno)),
+            None => bail!("failed to create device node"),
+        }
+    }
+}
+
+fn cmd_intercept(args: &clap::ArgMatches) -> Result<(), Error> {
+    let container = running_container(args)?;
+    let devices = match args.values_of("mknod") {
+        Some(values) => values.map(parse_device).collect::<Result<_, _>>()?,
+        None => Vec::new(),
+    };
+
+    let mknod = move |request: &lxc::SeccompRequest| {
+        let call = MknodCall::new(request);
+        let (major, minor) = (libc::major(call.dev), libc::minor(call.dev));
+        if !devices.contains(&(call.mode & libc::S_IFMT, major, minor)) {
+            // Leave it to the kernel, which usually refuses.
+            return lxc::SeccompResponse::Continue;
+        }
+        call.emulate(request).unwrap_or_else(|err| {
+            eprintln!("error: failed to emulate mknod: {}", err);
+            lxc::SeccompResponse::Errno(libc::EPERM)
+        })
+    };
+
+    let fd = container.seccomp_notify_fd_active()?;
+    // Calls of other architectures, e.g. of 32-bit tasks, have different
+    // syscall numbers and argument layouts and are left to the kernel.
+    let arch = lxc::AUDIT_ARCH_NATIVE;
+    let mut supervisor = lxc::SeccompSupervisor::new(fd);
+    #[cfg(target_arch = "x86_64")]
+    {
+        supervisor = supervisor.handle(arch, libc::SYS_mknod, mknod.clone());
+    }
+    supervisor = supervisor.handle(arch, libc::SYS_mknodat, mknod);
+    supervisor.run()
 }
 
 fn do_cmd(
@@ -419,16 +1407,149 @@ fn main() {
     }
 
     match matches.subcommand() {
+        ("create", Some(args)) => do_cmd("create", args, cmd_create),
+        ("copy", Some(args)) => do_cmd("copy", args, cmd_copy),
+        ("rename", Some(args)) => do_cmd("rename", args, cmd_rename),
+        ("destroy", Some(args)) => do_cmd("destroy", args, cmd_destroy),
         ("start", Some(args)) => do_cmd("start", args, cmd_start),
         ("stop", Some(args)) => do_cmd("stop", args, cmd_stop),
+        ("kill", Some(args)) => do_cmd("kill", args, cmd_kill),
+        ("restart", Some(args)) => do_cmd("restart", args, cmd_restart),
         ("list", Some(args)) => do_cmd("list", args, cmd_list),
         ("login", Some(args)) => do_cmd("login", args, cmd_login),
+        ("console-log", Some(args)) => {
+            do_cmd("console-log", args, cmd_console_log)
+        }
         ("freeze", Some(args)) => do_cmd("freeze", args, cmd_freeze),
         ("unfreeze", Some(args)) => do_cmd("unfreeze", args, cmd_unfreeze),
         ("exec", Some(args)) => exit(cmd_exec(args)),
+        ("checkpoint", Some(args)) => {
+            do_cmd("checkpoint", args, cmd_checkpoint)
+        }
+        ("restore", Some(args)) => do_cmd("restore", args, cmd_restore),
+        ("migrate", Some(args)) => do_cmd("migrate", args, cmd_migrate),
+        ("wait", Some(args)) => do_cmd("wait", args, cmd_wait),
+        ("mount", Some(args)) => do_cmd("mount", args, cmd_mount),
+        ("intercept", Some(args)) => do_cmd("intercept", args, cmd_intercept),
+        ("snapshot", Some(args)) => match args.subcommand() {
+            ("create", Some(args)) => {
+                do_cmd("snapshot", args, cmd_snapshot_create)
+            }
+            ("list", Some(args)) => do_cmd("snapshot", args, cmd_snapshot_list),
+            ("restore", Some(args)) => {
+                do_cmd("snapshot", args, cmd_snapshot_restore)
+            }
+            ("delete", Some(args)) => {
+                do_cmd("snapshot", args, cmd_snapshot_delete)
+            }
+            _ => {
+                println!("{}", args.usage());
+                exit(1);
+            }
+        },
+        ("config", Some(args)) => match args.subcommand() {
+            ("get", Some(args)) => do_cmd("config", args, cmd_config_get),
+            ("set", Some(args)) => do_cmd("config", args, cmd_config_set),
+            ("unset", Some(args)) => do_cmd("config", args, cmd_config_unset),
+            ("show", Some(args)) => do_cmd("config", args, cmd_config_show),
+            ("diff", Some(args)) => do_cmd("config", args, cmd_config_diff),
+            _ => {
+                println!("{}", args.usage());
+                exit(1);
+            }
+        },
+        ("cgroup", Some(args)) => match args.subcommand() {
+            ("get", Some(args)) => do_cmd("cgroup", args, cmd_cgroup_get),
+            ("set", Some(args)) => do_cmd("cgroup", args, cmd_cgroup_set),
+            _ => {
+                println!("{}", args.usage());
+                exit(1);
+            }
+        },
+        ("device", Some(args)) => match args.subcommand() {
+            ("add", Some(args)) => do_cmd("device", args, cmd_device_add),
+            ("remove", Some(args)) => do_cmd("device", args, cmd_device_remove),
+            _ => {
+                println!("{}", args.usage());
+                exit(1);
+            }
+        },
+        ("network", Some(args)) => match args.subcommand() {
+            ("attach", Some(args)) => {
+                do_cmd("network", args, cmd_network_attach)
+            }
+            ("detach", Some(args)) => {
+                do_cmd("network", args, cmd_network_detach)
+            }
+            _ => {
+                println!("{}", args.usage());
+                exit(1);
+            }
+        },
         _ => {
             println!("{}", matches.usage());
             exit(1);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("512").unwrap(), 512);
+        assert_eq!(parse_size("1K").unwrap(), 1 << 10);
+        assert_eq!(parse_size("2m").unwrap(), 2 << 20);
+        assert_eq!(parse_size("3G").unwrap(), 3 << 30);
+        assert_eq!(parse_size("1t").unwrap(), 1 << 40);
+        assert!(parse_size("").is_err());
+        assert!(parse_size("M").is_err());
+        assert!(parse_size("1X").is_err());
+        assert!(parse_size("-1").is_err());
+        assert!(parse_size("16777216T").is_err());
+    }
+
+    #[test]
+    fn test_parse_escape() {
+        assert_eq!(parse_escape("^a").unwrap(), 1);
+        assert_eq!(parse_escape("a").unwrap(), 1);
+        assert_eq!(parse_escape("^Z").unwrap(), 26);
+        assert_eq!(parse_escape("t").unwrap(), 20);
+        assert!(parse_escape("").is_err());
+        assert!(parse_escape("^").is_err());
+        assert!(parse_escape("^ab").is_err());
+        assert!(parse_escape("^1").is_err());
+    }
+
+    #[test]
+    fn test_parse_device() {
+        assert!(parse_device("c").is_err());
+        assert!(parse_device("c:1").is_err());
+        assert_eq!(parse_device("c:1:3").unwrap(), (libc::S_IFCHR, 1, 3));
+        assert_eq!(parse_device("b:8:0").unwrap(), (libc::S_IFBLK, 8, 0));
+        assert!(parse_device("x:1:3").is_err());
+        assert!(parse_device("c:a:3").is_err());
+        assert!(parse_device("c:1:-3").is_err());
+        assert!(parse_device("c:1:3:4").is_err());
+    }
+
+    #[test]
+    fn test_parse_signal() {
+        assert_eq!(parse_signal("9").unwrap(), libc::SIGKILL);
+        assert_eq!(parse_signal("0").unwrap(), 0);
+        assert!(parse_signal("-1").is_err());
+        assert_eq!(parse_signal("TERM").unwrap(), libc::SIGTERM);
+        assert_eq!(parse_signal("SIGTERM").unwrap(), libc::SIGTERM);
+        assert_eq!(parse_signal("sigkill").unwrap(), libc::SIGKILL);
+        assert_eq!(parse_signal("IOT").unwrap(), libc::SIGABRT);
+        assert_eq!(parse_signal("POLL").unwrap(), libc::SIGIO);
+        assert_eq!(parse_signal("RTMIN").unwrap(), libc::SIGRTMIN());
+        assert_eq!(parse_signal("SIGRTMIN+2").unwrap(), libc::SIGRTMIN() + 2);
+        assert_eq!(parse_signal("RTMAX-1").unwrap(), libc::SIGRTMAX() - 1);
+        assert!(parse_signal("RTMIN+100").is_err());
+        assert!(parse_signal("RTMAX-x").is_err());
+        assert!(parse_signal("FOO").is_err());
+    }
+}
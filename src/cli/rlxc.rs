@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: LGPL-2.1+
 
-use clap::{App, Arg, SubCommand};
+use clap::{App, AppSettings, Arg, SubCommand};
 
 pub fn build_cli() -> App<'static, 'static> {
     App::new("rlxc")
@@ -42,6 +42,22 @@ pub fn build_cli() -> App<'static, 'static> {
                         .index(1)
                         .help("Name of the container")
                         .required(true),
+                )
+                .arg(
+                    Arg::with_name("tty")
+                        .short("t")
+                        .long("tty")
+                        .help("tty to attach to (default 0, the console; -1 for the first unused tty)")
+                        .takes_value(true)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("escape")
+                        .short("e")
+                        .long("escape")
+                        .help("Escape character (default ^a)")
+                        .takes_value(true)
+                        .required(false),
                 ),
         )
         .subcommand(
@@ -197,4 +213,822 @@ pub fn build_cli() -> App<'static, 'static> {
                         .conflicts_with("name"),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("create")
+                .about("Create LXC containers")
+                .arg(
+                    Arg::with_name("name")
+                        .index(1)
+                        .help("Name of the container")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("template")
+                        .short("t")
+                        .long("template")
+                        .help("Template to use to create the container")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("bdev")
+                        .short("B")
+                        .long("bdev")
+                        .help("Backing store type to use (default dir)")
+                        .takes_value(true)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("fstype")
+                        .long("fstype")
+                        .help("Filesystem type for block based backing stores")
+                        .takes_value(true)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("fssize")
+                        .long("fssize")
+                        .help("Size of the backing store (e.g. 10G)")
+                        .takes_value(true)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("dir")
+                        .long("dir")
+                        .help("Directory to use as the container's rootfs")
+                        .takes_value(true)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("zfsroot")
+                        .long("zfsroot")
+                        .help("Parent dataset for zfs backed containers")
+                        .takes_value(true)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("vgname")
+                        .long("vgname")
+                        .help("Volume group for lvm backed containers")
+                        .takes_value(true)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("lvname")
+                        .long("lvname")
+                        .help("Logical volume name for lvm backed containers")
+                        .takes_value(true)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("thinpool")
+                        .long("thinpool")
+                        .help("Thin pool for lvm backed containers")
+                        .takes_value(true)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("quiet")
+                        .short("q")
+                        .long("quiet")
+                        .help("Suppress template output")
+                        .takes_value(false)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("template-args")
+                        .index(2)
+                        .help("Arguments passed to the template")
+                        .takes_value(true)
+                        .required(false)
+                        .multiple(true)
+                        .last(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("destroy")
+                .about("Destroy LXC containers")
+                .arg(
+                    Arg::with_name("name")
+                        .index(1)
+                        .help("Name of the container")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("force")
+                        .short("f")
+                        .long("force")
+                        .help("Stop the container first if it is running")
+                        .takes_value(false)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("snapshots")
+                        .short("s")
+                        .long("snapshots")
+                        .help("Destroy the container's snapshots as well")
+                        .takes_value(false)
+                        .required(false),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("copy")
+                .about("Copy LXC containers")
+                .arg(
+                    Arg::with_name("name")
+                        .index(1)
+                        .help("Name of the original container")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("newname")
+                        .index(2)
+                        .help("Name of the new container")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("snapshot")
+                        .short("s")
+                        .long("snapshot")
+                        .help("Create a copy-on-write snapshot of the container")
+                        .takes_value(false)
+                        .required(false)
+                        .conflicts_with("maybe-snapshot"),
+                )
+                .arg(
+                    Arg::with_name("maybe-snapshot")
+                        .long("maybe-snapshot")
+                        .help("Snapshot if the backing store supports it, copy otherwise")
+                        .takes_value(false)
+                        .required(false)
+                        .conflicts_with("snapshot"),
+                )
+                .arg(
+                    Arg::with_name("keepname")
+                        .short("K")
+                        .long("keepname")
+                        .help("Keep the hostname of the original container")
+                        .takes_value(false)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("keepmac")
+                        .short("M")
+                        .long("keepmac")
+                        .help("Keep the MAC addresses of the original container")
+                        .takes_value(false)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("keepbdevtype")
+                        .long("keepbdevtype")
+                        .help("Keep the backing store type of the original container")
+                        .takes_value(false)
+                        .required(false)
+                        .conflicts_with("bdev"),
+                )
+                .arg(
+                    Arg::with_name("newpath")
+                        .short("P")
+                        .long("newpath")
+                        .help("Base path for the new container")
+                        .takes_value(true)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("bdev")
+                        .short("B")
+                        .long("bdev")
+                        .help("Backing store type for the new container")
+                        .takes_value(true)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("fssize")
+                        .short("L")
+                        .long("fssize")
+                        .help("Size of the new container's backing store (e.g. 10G)")
+                        .takes_value(true)
+                        .required(false),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("snapshot")
+                .about("Manage snapshots of LXC containers")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("create")
+                        .about("Create a snapshot of a container")
+                        .arg(
+                            Arg::with_name("name")
+                                .index(1)
+                                .help("Name of the container")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("comment")
+                                .short("c")
+                                .long("comment")
+                                .help("File containing a comment for the snapshot")
+                                .takes_value(true)
+                                .required(false),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("list")
+                        .about("List snapshots of a container")
+                        .arg(
+                            Arg::with_name("name")
+                                .index(1)
+                                .help("Name of the container")
+                                .required(true),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("restore")
+                        .about("Restore a snapshot of a container")
+                        .arg(
+                            Arg::with_name("name")
+                                .index(1)
+                                .help("Name of the container")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("snapshot")
+                                .index(2)
+                                .help("Name of the snapshot")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("newname")
+                                .index(3)
+                                .help("Name of the restored container (default: replace the container)")
+                                .required(false),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("delete")
+                        .about("Delete snapshots of a container")
+                        .arg(
+                            Arg::with_name("name")
+                                .index(1)
+                                .help("Name of the container")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("snapshot")
+                                .index(2)
+                                .help("Name of the snapshot")
+                                .required_unless("all"),
+                        )
+                        .arg(
+                            Arg::with_name("all")
+                                .long("all")
+                                .help("delete all snapshots")
+                                .takes_value(false)
+                                .required(false)
+                                .conflicts_with("snapshot"),
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("config")
+                .about("Manage the configuration of LXC containers")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("get")
+                        .about("Get a configuration item")
+                        .arg(
+                            Arg::with_name("name")
+                                .index(1)
+                                .help("Name of the container")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("key")
+                                .index(2)
+                                .help("Configuration key")
+                                .required(true),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("set")
+                        .about("Set a configuration item")
+                        .arg(
+                            Arg::with_name("name")
+                                .index(1)
+                                .help("Name of the container")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("key")
+                                .index(2)
+                                .help("Configuration key")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("value")
+                                .index(3)
+                                .help("Configuration value")
+                                .required(true),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("unset")
+                        .about("Clear a configuration item")
+                        .arg(
+                            Arg::with_name("name")
+                                .index(1)
+                                .help("Name of the container")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("key")
+                                .index(2)
+                                .help("Configuration key")
+                                .required(true),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("diff")
+                        .about("Show differences between the running and saved configuration")
+                        .arg(
+                            Arg::with_name("name")
+                                .index(1)
+                                .help("Name of the container")
+                                .required(true),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("show")
+                        .about("Show the configuration of a container")
+                        .arg(
+                            Arg::with_name("name")
+                                .index(1)
+                                .help("Name of the container")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("prefix")
+                                .index(2)
+                                .help("Only show keys below this prefix (e.g. lxc.net.0)")
+                                .required(false),
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("cgroup")
+                .about("Manage cgroup settings of running LXC containers")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("get")
+                        .about("Get the value of a cgroup file")
+                        .arg(
+                            Arg::with_name("name")
+                                .index(1)
+                                .help("Name of the container")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("key")
+                                .index(2)
+                                .help("Cgroup file (e.g. memory.current)")
+                                .required(true),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("set")
+                        .about("Set the value of a cgroup file")
+                        .arg(
+                            Arg::with_name("name")
+                                .index(1)
+                                .help("Name of the container")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("key")
+                                .index(2)
+                                .help("Cgroup file (e.g. pids.max)")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("value")
+                                .index(3)
+                                .help("Value to write")
+                                .required(true),
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("checkpoint")
+                .about("Checkpoint running LXC containers")
+                .arg(
+                    Arg::with_name("name")
+                        .index(1)
+                        .help("Name of the container")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("directory")
+                        .short("D")
+                        .long("directory")
+                        .help("Directory to write the checkpoint to")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("stop")
+                        .short("s")
+                        .long("stop")
+                        .help("Stop the container after checkpointing it")
+                        .takes_value(false)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("pre-dump")
+                        .long("pre-dump")
+                        .help("Only dump the container's memory and keep it running")
+                        .takes_value(false)
+                        .required(false)
+                        .conflicts_with("stop"),
+                )
+                .arg(
+                    Arg::with_name("predump-dir")
+                        .long("predump-dir")
+                        .help("Directory of a previous pre-dump (relative to --directory)")
+                        .takes_value(true)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("action-script")
+                        .long("action-script")
+                        .help("Script CRIU runs during the checkpoint")
+                        .takes_value(true)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("ghost-limit")
+                        .long("ghost-limit")
+                        .help("Maximum size of deleted files to include (e.g. 1M)")
+                        .takes_value(true)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("verbose")
+                        .short("v")
+                        .long("verbose")
+                        .help("Enable verbose CRIU logging")
+                        .takes_value(false)
+                        .required(false),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("restore")
+                .about("Restore LXC containers from a checkpoint")
+                .arg(
+                    Arg::with_name("name")
+                        .index(1)
+                        .help("Name of the container")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("directory")
+                        .short("D")
+                        .long("directory")
+                        .help("Directory to restore the checkpoint from")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("verbose")
+                        .short("v")
+                        .long("verbose")
+                        .help("Enable verbose CRIU logging")
+                        .takes_value(false)
+                        .required(false),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("migrate")
+                .about("Move running LXC containers to another path")
+                .arg(
+                    Arg::with_name("name")
+                        .index(1)
+                        .help("Name of the container")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("to-path")
+                        .long("to-path")
+                        .help("Base path to move the container to")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("directory")
+                        .short("D")
+                        .long("directory")
+                        .help("Directory to store the checkpoints in")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("max-iterations")
+                        .long("max-iterations")
+                        .help("Maximum number of pre-dumps (default 10)")
+                        .takes_value(true)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("threshold")
+                        .long("threshold")
+                        .help("Stop pre-dumping once less memory than this changed (default 1M)")
+                        .takes_value(true)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("verbose")
+                        .short("v")
+                        .long("verbose")
+                        .help("Enable verbose CRIU logging")
+                        .takes_value(false)
+                        .required(false),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("wait")
+                .about("Wait for LXC containers to reach a state")
+                .arg(
+                    Arg::with_name("name")
+                        .index(1)
+                        .help("Name of the container")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("state")
+                        .short("s")
+                        .long("state")
+                        .help("State to wait for (e.g. RUNNING)")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("timeout")
+                        .short("t")
+                        .long("timeout")
+                        .help("timeout to wait for the state (default -1, wait forever)")
+                        .takes_value(true)
+                        .required(false),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("restart")
+                .about("Restart LXC containers")
+                .arg(
+                    Arg::with_name("name")
+                        .index(1)
+                        .help("Name of the container")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("force")
+                        .short("f")
+                        .long("force")
+                        .help("SIGKILL the container and start it again")
+                        .takes_value(false)
+                        .required(false)
+                        .conflicts_with("timeout"),
+                )
+                .arg(
+                    Arg::with_name("timeout")
+                        .short("t")
+                        .long("timeout")
+                        .help("timeout to wait for the reboot before stopping and starting the container (default: 60, -1 to wait forever)")
+                        .takes_value(true)
+                        .required(false)
+                        .conflicts_with("force"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("rename")
+                .about("Rename LXC containers")
+                .arg(
+                    Arg::with_name("name")
+                        .index(1)
+                        .help("Name of the container")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("newname")
+                        .index(2)
+                        .help("New name of the container")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("device")
+                .about("Manage device nodes of running LXC containers")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("add")
+                        .about("Add a device node to a container")
+                        .arg(
+                            Arg::with_name("name")
+                                .index(1)
+                                .help("Name of the container")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("src_path")
+                                .index(2)
+                                .help("Path of the device node on the host")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("dest_path")
+                                .index(3)
+                                .help("Path of the device node in the container (default: src_path)")
+                                .required(false),
+                        )
+                        .arg(
+                            Arg::with_name("persist")
+                                .long("persist")
+                                .help("Add the device to the container's configuration as well")
+                                .takes_value(false)
+                                .required(false),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("remove")
+                        .about("Remove a device node from a container")
+                        .arg(
+                            Arg::with_name("name")
+                                .index(1)
+                                .help("Name of the container")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("src_path")
+                                .index(2)
+                                .help("Path of the device node on the host")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("dest_path")
+                                .index(3)
+                                .help("Path of the device node in the container (default: src_path)")
+                                .required(false),
+                        )
+                        .arg(
+                            Arg::with_name("persist")
+                                .long("persist")
+                                .help("Remove the device from the container's configuration as well")
+                                .takes_value(false)
+                                .required(false),
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("network")
+                .about("Manage network interfaces of running LXC containers")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("attach")
+                        .about("Move a host network interface into a container")
+                        .arg(
+                            Arg::with_name("name")
+                                .index(1)
+                                .help("Name of the container")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("host_ifname")
+                                .index(2)
+                                .help("Name of the interface on the host")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("container_ifname")
+                                .index(3)
+                                .help("Name of the interface in the container (default: host_ifname)")
+                                .required(false),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("detach")
+                        .about("Move a network interface from a container back to the host")
+                        .arg(
+                            Arg::with_name("name")
+                                .index(1)
+                                .help("Name of the container")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("host_ifname")
+                                .index(2)
+                                .help("Name of the interface on the host")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("container_ifname")
+                                .index(3)
+                                .help("Name of the interface in the container (default: host_ifname)")
+                                .required(false),
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("mount")
+                .about("Bind-mount host paths into running LXC containers")
+                .after_help("The container needs lxc.mount.auto = shmounts:<path> to be set. Read-only mounts need root and don't include submounts of host_path.")
+                .arg(
+                    Arg::with_name("name")
+                        .index(1)
+                        .help("Name of the container")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("host_path")
+                        .index(2)
+                        .help("Path on the host")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("container_path")
+                        .index(3)
+                        .help("Path in the container")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("readonly")
+                        .short("r")
+                        .long("readonly")
+                        .help("Mount read-only, without submounts (needs root)")
+                        .takes_value(false)
+                        .required(false),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("console-log")
+                .about("Show the console log of running LXC containers")
+                .arg(
+                    Arg::with_name("name")
+                        .index(1)
+                        .help("Name of the container")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("clear")
+                        .short("c")
+                        .long("clear")
+                        .help("Clear the console log after showing it")
+                        .takes_value(false)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("follow")
+                        .short("f")
+                        .long("follow")
+                        .help("Keep showing new output")
+                        .takes_value(false)
+                        .required(false),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("intercept")
+                .about("Handle syscalls intercepted in running LXC containers")
+                .arg(
+                    Arg::with_name("name")
+                        .index(1)
+                        .help("Name of the container")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("mknod")
+                        .long("mknod")
+                        .help("Create the device <c|b>:<major>:<minor> on mknod")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .required(false),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("kill")
+                .about("Send a signal to the init process of LXC containers")
+                .arg(
+                    Arg::with_name("name")
+                        .index(1)
+                        .help("Name of the container")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("signal")
+                        .short("s")
+                        .long("signal")
+                        .help("Signal to send as name or number")
+                        .takes_value(true)
+                        .default_value("SIGTERM"),
+                ),
+        )
 }
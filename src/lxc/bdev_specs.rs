@@ -0,0 +1,150 @@
+// SPDX-License-Identifier: LGPL-2.1+
+
+use crate::util::ffi::ToCString;
+use std::ffi::{CString, NulError, OsStr};
+use std::ptr;
+
+/// Type representing the backing store specifications used when creating a
+/// container. This is a wrapper for liblxc's `struct bdev_specs`.
+pub struct BdevSpecs {
+    raw: lxc_sys::bdev_specs,
+    fstype: Option<CString>,
+    fssize: u64,
+    dir: Option<CString>,
+    zfsroot: Option<CString>,
+    vg: Option<CString>,
+    lv: Option<CString>,
+    thinpool: Option<CString>,
+}
+
+impl BdevSpecs {
+    pub fn new() -> Self {
+        Self {
+            raw: unsafe { std::mem::zeroed() },
+            fstype: None,
+            fssize: 0,
+            dir: None,
+            zfsroot: None,
+            vg: None,
+            lv: None,
+            thinpool: None,
+        }
+    }
+}
+
+impl Default for BdevSpecs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BdevSpecs {
+    /// Filesystem type to create on block based backing stores.
+    pub fn set_fstype<T: AsRef<OsStr>>(
+        mut self,
+        v: T,
+    ) -> Result<Self, NulError> {
+        let cv = v.as_ref().to_c_string()?;
+
+        self.fstype = Some(cv.into_owned());
+        Ok(self)
+    }
+
+    /// Size of the backing store in bytes. Pass `0` to use liblxc's default.
+    pub fn set_fssize(mut self, v: u64) -> Self {
+        self.fssize = v;
+        self
+    }
+
+    /// Directory to use as the container's rootfs.
+    pub fn set_dir<T: AsRef<OsStr>>(mut self, v: T) -> Result<Self, NulError> {
+        let cv = v.as_ref().to_c_string()?;
+
+        self.dir = Some(cv.into_owned());
+        Ok(self)
+    }
+
+    /// Parent dataset for zfs backed containers.
+    pub fn set_zfsroot<T: AsRef<OsStr>>(
+        mut self,
+        v: T,
+    ) -> Result<Self, NulError> {
+        let cv = v.as_ref().to_c_string()?;
+
+        self.zfsroot = Some(cv.into_owned());
+        Ok(self)
+    }
+
+    /// Volume group for lvm backed containers.
+    pub fn set_lvm_vg<T: AsRef<OsStr>>(
+        mut self,
+        v: T,
+    ) -> Result<Self, NulError> {
+        let cv = v.as_ref().to_c_string()?;
+
+        self.vg = Some(cv.into_owned());
+        Ok(self)
+    }
+
+    /// Logical volume name for lvm backed containers. Defaults to the
+    /// container name.
+    pub fn set_lvm_lv<T: AsRef<OsStr>>(
+        mut self,
+        v: T,
+    ) -> Result<Self, NulError> {
+        let cv = v.as_ref().to_c_string()?;
+
+        self.lv = Some(cv.into_owned());
+        Ok(self)
+    }
+
+    /// Thin pool for lvm backed containers.
+    pub fn set_lvm_thinpool<T: AsRef<OsStr>>(
+        mut self,
+        v: T,
+    ) -> Result<Self, NulError> {
+        let cv = v.as_ref().to_c_string()?;
+
+        self.thinpool = Some(cv.into_owned());
+        Ok(self)
+    }
+
+    fn finish(&mut self) {
+        self.raw.fstype = self
+            .fstype
+            .as_ref()
+            .map(|s| s.as_ptr() as *mut _)
+            .unwrap_or(ptr::null_mut());
+        self.raw.fssize = self.fssize;
+        self.raw.dir = self
+            .dir
+            .as_ref()
+            .map(|s| s.as_ptr() as *mut _)
+            .unwrap_or(ptr::null_mut());
+        self.raw.zfs.zfsroot = self
+            .zfsroot
+            .as_ref()
+            .map(|s| s.as_ptr() as *mut _)
+            .unwrap_or(ptr::null_mut());
+        self.raw.lvm.vg = self
+            .vg
+            .as_ref()
+            .map(|s| s.as_ptr() as *mut _)
+            .unwrap_or(ptr::null_mut());
+        self.raw.lvm.lv = self
+            .lv
+            .as_ref()
+            .map(|s| s.as_ptr() as *mut _)
+            .unwrap_or(ptr::null_mut());
+        self.raw.lvm.thinpool = self
+            .thinpool
+            .as_ref()
+            .map(|s| s.as_ptr() as *mut _)
+            .unwrap_or(ptr::null_mut());
+    }
+
+    pub fn raw(&mut self) -> &mut lxc_sys::bdev_specs {
+        self.finish();
+        &mut self.raw
+    }
+}
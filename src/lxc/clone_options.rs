@@ -0,0 +1,119 @@
+// SPDX-License-Identifier: LGPL-2.1+
+
+use crate::util::ffi::ToCString;
+use std::ffi::{CString, NulError, OsStr};
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+
+/// Type representing options for how to clone a container.
+pub struct CloneOptions {
+    flags: c_int,
+    lxcpath: Option<CString>,
+    bdevtype: Option<CString>,
+    newsize: u64,
+}
+
+impl CloneOptions {
+    pub fn new() -> Self {
+        Self {
+            flags: 0,
+            lxcpath: None,
+            bdevtype: None,
+            newsize: 0,
+        }
+    }
+}
+
+impl Default for CloneOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CloneOptions {
+    pub fn clone_flag(mut self, flag: c_int, on: bool) -> Self {
+        if on {
+            self.flags |= flag;
+        } else {
+            self.flags &= !flag;
+        }
+        self
+    }
+
+    /// Create a copy-on-write snapshot instead of copying the rootfs.
+    pub fn snapshot(self, on: bool) -> Self {
+        self.clone_flag(lxc_sys::LXC_CLONE_SNAPSHOT as _, on)
+    }
+
+    /// Do not change the hostname of the new container.
+    pub fn keep_name(self, on: bool) -> Self {
+        self.clone_flag(lxc_sys::LXC_CLONE_KEEPNAME as _, on)
+    }
+
+    /// Do not generate new MAC addresses for the new container.
+    pub fn keep_macaddr(self, on: bool) -> Self {
+        self.clone_flag(lxc_sys::LXC_CLONE_KEEPMACADDR as _, on)
+    }
+
+    /// Use the same backing store type as the original container.
+    pub fn keep_bdevtype(self, on: bool) -> Self {
+        self.clone_flag(lxc_sys::LXC_CLONE_KEEPBDEVTYPE as _, on)
+    }
+
+    /// Create a snapshot if the backing store supports it, copy otherwise.
+    pub fn maybe_snapshot(self, on: bool) -> Self {
+        self.clone_flag(lxc_sys::LXC_CLONE_MAYBE_SNAPSHOT as _, on)
+    }
+
+    /// Place the new container under `v` instead of the original container's
+    /// path.
+    pub fn set_lxcpath<T: AsRef<OsStr>>(
+        mut self,
+        v: T,
+    ) -> Result<Self, NulError> {
+        let cv = v.as_ref().to_c_string()?;
+
+        self.lxcpath = Some(cv.into_owned());
+        Ok(self)
+    }
+
+    /// Backing store type to use for the new container.
+    pub fn set_bdevtype<T: AsRef<OsStr>>(
+        mut self,
+        v: T,
+    ) -> Result<Self, NulError> {
+        let cv = v.as_ref().to_c_string()?;
+
+        self.bdevtype = Some(cv.into_owned());
+        Ok(self)
+    }
+
+    /// Size of the new container's backing store in bytes. Pass `0` to use
+    /// the size of the original container.
+    pub fn set_newsize(mut self, v: u64) -> Self {
+        self.newsize = v;
+        self
+    }
+
+    pub(crate) fn flags(&self) -> c_int {
+        self.flags
+    }
+
+    pub(crate) fn lxcpath(&self) -> *const c_char {
+        self.lxcpath
+            .as_ref()
+            .map(|s| s.as_ptr())
+            .unwrap_or(ptr::null())
+    }
+
+    pub(crate) fn bdevtype(&self) -> *const c_char {
+        self.bdevtype
+            .as_ref()
+            .map(|s| s.as_ptr())
+            .unwrap_or(ptr::null())
+    }
+
+    pub(crate) fn newsize(&self) -> u64 {
+        self.newsize
+    }
+}
@@ -0,0 +1,323 @@
+// SPDX-License-Identifier: LGPL-2.1+
+
+//! Spawning processes in a container without waiting for them.
+
+use anyhow::{bail, Error};
+use std::ffi::{OsStr, OsString};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read};
+use std::os::raw::c_int;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::os::unix::process::ExitStatusExt;
+use std::process::{ExitStatus, Output};
+use std::ptr;
+
+use super::{AttachOptions, Lxc};
+use crate::util::ffi::ToCString;
+
+/// What to connect a standard stream of a `ContainerCommand` to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stdio {
+    /// Use the stream of the caller (the default).
+    Inherit,
+    /// Create a pipe, available through `ContainerChild`.
+    Piped,
+    /// Connect the stream to `/dev/null`.
+    Null,
+}
+
+impl Stdio {
+    /// Returns the fd to pass to the attached process and, for pipes, the
+    /// caller's end. The first fd is kept open by the returned files.
+    fn setup(
+        self,
+        default: RawFd,
+        input: bool,
+    ) -> io::Result<(RawFd, Option<File>, Option<File>)> {
+        match self {
+            Stdio::Inherit => Ok((default, None, None)),
+            Stdio::Null => {
+                let null = OpenOptions::new()
+                    .read(input)
+                    .write(!input)
+                    .open("/dev/null")?;
+                Ok((null.as_raw_fd(), Some(null), None))
+            }
+            Stdio::Piped => {
+                let mut fds = [-1; 2];
+                if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } < 0
+                {
+                    return Err(io::Error::last_os_error());
+                }
+                let read = unsafe { File::from_raw_fd(fds[0]) };
+                let write = unsafe { File::from_raw_fd(fds[1]) };
+                if input {
+                    Ok((read.as_raw_fd(), Some(read), Some(write)))
+                } else {
+                    Ok((write.as_raw_fd(), Some(write), Some(read)))
+                }
+            }
+        }
+    }
+}
+
+/// Builder for a process to run in a container, similar to
+/// `std::process::Command`. Created via `Lxc::command`.
+pub struct ContainerCommand<'a> {
+    container: &'a Lxc,
+    program: OsString,
+    args: Vec<OsString>,
+    options: AttachOptions<'static, 'static, 'static, 'static>,
+    // Unset streams default to `Stdio::Inherit`, except for `output`.
+    stdin: Option<Stdio>,
+    stdout: Option<Stdio>,
+    stderr: Option<Stdio>,
+}
+
+impl<'a> ContainerCommand<'a> {
+    pub(crate) fn new<T: AsRef<OsStr>>(container: &'a Lxc, program: T) -> Self {
+        Self {
+            container,
+            program: program.as_ref().to_owned(),
+            args: Vec::new(),
+            options: AttachOptions::new(),
+            stdin: None,
+            stdout: None,
+            stderr: None,
+        }
+    }
+
+    pub fn arg<T: AsRef<OsStr>>(mut self, arg: T) -> Self {
+        self.args.push(arg.as_ref().to_owned());
+        self
+    }
+
+    pub fn args<I, T>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<OsStr>,
+    {
+        self.args
+            .extend(args.into_iter().map(|a| a.as_ref().to_owned()));
+        self
+    }
+
+    /// Options for attaching to the container. The standard streams set on
+    /// them are replaced by the ones of this command and no terminal is
+    /// allocated.
+    pub fn options(
+        mut self,
+        options: AttachOptions<'static, 'static, 'static, 'static>,
+    ) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn stdin(mut self, v: Stdio) -> Self {
+        self.stdin = Some(v);
+        self
+    }
+
+    pub fn stdout(mut self, v: Stdio) -> Self {
+        self.stdout = Some(v);
+        self
+    }
+
+    pub fn stderr(mut self, v: Stdio) -> Self {
+        self.stderr = Some(v);
+        self
+    }
+
+    /// Start the process without waiting for it.
+    pub fn spawn(&mut self) -> Result<ContainerChild, Error> {
+        self.spawn_with_defaults([Stdio::Inherit; 3])
+    }
+
+    /// Run the process and wait for it to exit.
+    pub fn status(&mut self) -> Result<ExitStatus, Error> {
+        self.spawn()?.wait()
+    }
+
+    /// Run the process and collect its output. Unless set otherwise its
+    /// standard input is `Stdio::Null` and its standard output and error are
+    /// piped.
+    pub fn output(&mut self) -> Result<Output, Error> {
+        self.spawn_with_defaults([Stdio::Null, Stdio::Piped, Stdio::Piped])?
+            .wait_with_output()
+    }
+
+    /// Spawn the process, using `defaults` for the streams which weren't set.
+    fn spawn_with_defaults(
+        &mut self,
+        defaults: [Stdio; 3],
+    ) -> Result<ContainerChild, Error> {
+        let mut argv: Vec<&OsStr> = vec![&self.program];
+        argv.extend(self.args.iter().map(|a| a.as_os_str()));
+        spawn(
+            self.container,
+            &mut self.options,
+            &self.program,
+            argv,
+            [
+                self.stdin.unwrap_or(defaults[0]),
+                self.stdout.unwrap_or(defaults[1]),
+                self.stderr.unwrap_or(defaults[2]),
+            ],
+        )
+    }
+}
+
+/// Run `program` in `container` with the standard streams set up as given by
+/// `stdio`.
+pub(crate) fn spawn(
+    container: &Lxc,
+    options: &mut AttachOptions,
+    program: &OsStr,
+    argv: Vec<&OsStr>,
+    stdio: [Stdio; 3],
+) -> Result<ContainerChild, Error> {
+    let cprogram = program.to_c_string()?;
+    let cargv = argv
+        .iter()
+        .map(|arg| arg.to_c_string())
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut args: Vec<_> = cargv.iter().map(|arg| arg.as_ptr()).collect();
+    args.push(ptr::null());
+    let mut command = lxc_sys::lxc_attach_command_t {
+        program: cprogram.as_ptr() as *mut _,
+        argv: args.as_ptr() as *mut _,
+    };
+
+    // The attached process' ends are closed when these go out of scope.
+    let (stdin_fd, _stdin, stdin) = stdio[0].setup(0, true)?;
+    let (stdout_fd, _stdout, stdout) = stdio[1].setup(1, false)?;
+    let (stderr_fd, _stderr, stderr) = stdio[2].setup(2, false)?;
+
+    let pid = unsafe {
+        container.attach_raw(
+            options,
+            Some(lxc_sys::lxc_attach_run_command),
+            &mut command as *mut lxc_sys::lxc_attach_command_t as *mut _,
+            Some([stdin_fd, stdout_fd, stderr_fd]),
+        )?
+    };
+    Ok(ContainerChild {
+        pid,
+        status: None,
+        stdin,
+        stdout,
+        stderr,
+    })
+}
+
+/// A process running in a container, similar to `std::process::Child`. The
+/// process is not killed or waited for when this is dropped.
+pub struct ContainerChild {
+    pid: libc::pid_t,
+    status: Option<ExitStatus>,
+    /// The process' standard input if it was set to `Stdio::Piped`.
+    pub stdin: Option<File>,
+    /// The process' standard output if it was set to `Stdio::Piped`.
+    pub stdout: Option<File>,
+    /// The process' standard error if it was set to `Stdio::Piped`.
+    pub stderr: Option<File>,
+}
+
+impl ContainerChild {
+    /// The pid of the process as seen from the caller.
+    pub fn id(&self) -> libc::pid_t {
+        self.pid
+    }
+
+    fn waitpid(&mut self, flags: c_int) -> Result<Option<ExitStatus>, Error> {
+        if let Some(status) = self.status {
+            return Ok(Some(status));
+        }
+
+        let mut status = 0;
+        loop {
+            let ret = unsafe { libc::waitpid(self.pid, &mut status, flags) };
+            if ret > 0 {
+                break;
+            }
+            if ret == 0 {
+                return Ok(None);
+            }
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                bail!("failed to wait for {}: {}", self.pid, err);
+            }
+        }
+
+        self.status = Some(ExitStatus::from_raw(status));
+        Ok(self.status)
+    }
+
+    /// Wait for the process to exit. Its standard input is closed first so it
+    /// doesn't wait for more input.
+    pub fn wait(&mut self) -> Result<ExitStatus, Error> {
+        drop(self.stdin.take());
+        match self.waitpid(0)? {
+            Some(status) => Ok(status),
+            None => bail!("failed to wait for {}", self.pid),
+        }
+    }
+
+    /// Wait for the process to exit and collect what it wrote to its piped
+    /// standard output and error.
+    pub fn wait_with_output(mut self) -> Result<Output, Error> {
+        drop(self.stdin.take());
+
+        // Read both pipes at the same time, the process might block on
+        // either of them otherwise.
+        let stderr = self.stderr.take().map(|mut file| {
+            std::thread::spawn(move || {
+                let mut data = Vec::new();
+                file.read_to_end(&mut data).map(|_| data)
+            })
+        });
+        let mut stdout = Vec::new();
+        if let Some(file) = self.stdout.as_mut() {
+            file.read_to_end(&mut stdout)?;
+        }
+        let stderr = match stderr {
+            Some(reader) => match reader.join() {
+                Ok(data) => data?,
+                Err(_) => bail!("failed to read standard error"),
+            },
+            None => Vec::new(),
+        };
+
+        Ok(Output {
+            status: self.wait()?,
+            stdout,
+            stderr,
+        })
+    }
+
+    /// Check whether the process exited without blocking.
+    pub fn try_wait(&mut self) -> Result<Option<ExitStatus>, Error> {
+        self.waitpid(libc::WNOHANG)
+    }
+
+    /// Send `signal` to the process.
+    pub fn signal(&mut self, signal: c_int) -> Result<(), Error> {
+        // The pid may have been recycled already.
+        if self.status.is_some() {
+            bail!("process {} already exited", self.pid);
+        }
+        if unsafe { libc::kill(self.pid, signal) } < 0 {
+            bail!(
+                "failed to signal {}: {}",
+                self.pid,
+                io::Error::last_os_error()
+            );
+        }
+        Ok(())
+    }
+
+    /// Kill the process with `SIGKILL`.
+    pub fn kill(&mut self) -> Result<(), Error> {
+        self.signal(libc::SIGKILL)
+    }
+}
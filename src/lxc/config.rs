@@ -0,0 +1,174 @@
+// SPDX-License-Identifier: LGPL-2.1+
+
+use anyhow::{bail, Error};
+use std::ffi::CStr;
+use std::path::Path;
+use std::ptr;
+
+use super::Lxc;
+use crate::util::ffi::{read_sized_string, ToCString};
+
+/// Accessor for the configuration of a container. Obtained via
+/// `Lxc::config`.
+pub struct Config<'a> {
+    container: &'a Lxc,
+}
+
+impl<'a> Config<'a> {
+    pub(crate) fn new(container: &'a Lxc) -> Self {
+        Self { container }
+    }
+
+    /// Get the value of the configuration item `key`. Items which may be
+    /// specified multiple times (e.g. `lxc.mount.entry`) have their values
+    /// separated by newlines.
+    pub fn get(&self, key: &str) -> Result<String, Error> {
+        let handle = self.container.handle;
+        let ckey = key.to_c_string()?;
+        match read_sized_string(|retv, inlen| unsafe {
+            (*handle).get_config_item.unwrap()(
+                handle,
+                ckey.as_ptr(),
+                retv,
+                inlen,
+            )
+        }) {
+            Some(value) => Ok(value),
+            None => bail!("failed to get config item {}", key),
+        }
+    }
+
+    /// Get the value of the configuration item `key` as currently used by the
+    /// running container. This may differ from `get` if the configuration
+    /// was changed after the container was started.
+    pub fn get_running(&self, key: &str) -> Result<String, Error> {
+        let handle = self.container.handle;
+        let ckey = key.to_c_string()?;
+        let value = unsafe {
+            (*handle).get_running_config_item.unwrap()(handle, ckey.as_ptr())
+        };
+        if value.is_null() {
+            bail!("failed to get running config item {}", key);
+        }
+
+        let s = unsafe { CStr::from_ptr(value) }
+            .to_string_lossy()
+            .into_owned();
+        unsafe { libc::free(value as *mut _) };
+        Ok(s)
+    }
+
+    /// Set the configuration item `key` to `value`. For items which may be
+    /// specified multiple times this adds another value.
+    pub fn set(&self, key: &str, value: &str) -> Result<(), Error> {
+        let handle = self.container.handle;
+        let ckey = key.to_c_string()?;
+        let cvalue = value.to_c_string()?;
+        let set = unsafe {
+            (*handle).set_config_item.unwrap()(
+                handle,
+                ckey.as_ptr(),
+                cvalue.as_ptr(),
+            )
+        };
+        if !set {
+            bail!("failed to set config item {}", key);
+        }
+        Ok(())
+    }
+
+    /// Clear the configuration item `key`, removing all of its values.
+    pub fn clear(&self, key: &str) -> Result<(), Error> {
+        let handle = self.container.handle;
+        let ckey = key.to_c_string()?;
+        let cleared = unsafe {
+            (*handle).clear_config_item.unwrap()(handle, ckey.as_ptr())
+        };
+        if !cleared {
+            bail!("failed to clear config item {}", key);
+        }
+        Ok(())
+    }
+
+    /// Remove a single `value` from the configuration item `key`, keeping its
+    /// other values.
+    pub fn remove(&self, key: &str, value: &str) -> Result<(), Error> {
+        let current = self.get(key)?;
+        let mut found = false;
+        let keep: Vec<_> = current
+            .lines()
+            .filter(|v| !v.is_empty())
+            .filter(|v| {
+                if !found && *v == value {
+                    found = true;
+                    return false;
+                }
+                true
+            })
+            .collect();
+        if !found {
+            bail!("config item {} has no value {}", key, value);
+        }
+
+        self.clear(key)?;
+        for v in keep {
+            self.set(key, v)?;
+        }
+        Ok(())
+    }
+
+    /// Get the configuration keys below `prefix` (e.g. `lxc.net.0`), or all
+    /// keys known to liblxc if `prefix` is `None`. The returned keys are
+    /// always fully qualified.
+    pub fn keys(&self, prefix: Option<&str>) -> Result<Vec<String>, Error> {
+        let handle = self.container.handle;
+        let cprefix = match prefix {
+            Some(p) => Some(p.to_c_string()?),
+            None => None,
+        };
+        let keys = match read_sized_string(|retv, inlen| unsafe {
+            (*handle).get_keys.unwrap()(
+                handle,
+                cprefix.as_ref().map_or(ptr::null(), |p| p.as_ptr()),
+                retv,
+                inlen,
+            )
+        }) {
+            Some(keys) => keys,
+            None => match prefix {
+                Some(p) => bail!("failed to get config keys for {}", p),
+                None => bail!("failed to get config keys"),
+            },
+        };
+
+        Ok(keys
+            .lines()
+            .filter(|k| !k.is_empty())
+            .map(|k| match prefix {
+                // liblxc returns subkeys relative to the prefix.
+                Some(p) if !k.starts_with(p) => format!("{}.{}", p, k),
+                _ => k.to_string(),
+            })
+            .collect())
+    }
+
+    /// Write the configuration to disk. If `alt_file` is `None` the
+    /// container's own configuration file is written.
+    pub fn save(&self, alt_file: Option<&Path>) -> Result<(), Error> {
+        let handle = self.container.handle;
+        let cfile = match alt_file {
+            Some(f) => Some(f.to_c_string()?),
+            None => None,
+        };
+        let saved = unsafe {
+            (*handle).save_config.unwrap()(
+                handle,
+                cfile.as_ref().map_or(ptr::null(), |f| f.as_ptr()),
+            )
+        };
+        if !saved {
+            bail!("failed to save config");
+        }
+        Ok(())
+    }
+}
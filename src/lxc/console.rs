@@ -0,0 +1,328 @@
+// SPDX-License-Identifier: LGPL-2.1+
+
+//! In-process proxy for a container's terminals.
+
+use anyhow::{bail, Error};
+use std::fs::File;
+use std::io;
+use std::os::raw::c_int;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::sync::atomic::{AtomicI32, Ordering};
+
+use super::TerminalOptions;
+use crate::util::tty;
+
+/// A tty of a container allocated through `Lxc::console_getfd`. The tty stays
+/// allocated until this is dropped.
+pub struct Console {
+    ttynum: c_int,
+    ptx: File,
+    // Connection to the container's command socket, closing it releases the
+    // tty.
+    _tty: File,
+}
+
+impl Console {
+    /// # Safety
+    ///
+    /// `ttyfd` and `ptxfd` must be valid file descriptors owned by the caller.
+    pub(crate) unsafe fn from_raw(
+        ttynum: c_int,
+        ttyfd: RawFd,
+        ptxfd: RawFd,
+    ) -> Self {
+        Self {
+            ttynum,
+            ptx: File::from_raw_fd(ptxfd),
+            _tty: File::from_raw_fd(ttyfd),
+        }
+    }
+
+    /// The number of the allocated tty.
+    pub fn ttynum(&self) -> c_int {
+        self.ttynum
+    }
+
+    /// The pty master of the allocated tty.
+    pub fn ptx(&self) -> &File {
+        &self.ptx
+    }
+
+    /// Forward input and output between the tty and the stdio file
+    /// descriptors of `options` until the user detaches or the tty is closed.
+    ///
+    /// If stdin is a terminal it is put into raw mode and size changes are
+    /// propagated to the tty. Typing the escape character followed by `q`
+    /// detaches, `b` sends a break, `c` sends `SIGINT`, `?` shows a help
+    /// message and the escape character itself sends the escape character.
+    pub fn proxy(&self, options: &TerminalOptions) -> Result<(), Error> {
+        proxy_terminal(&self.ptx, options)
+    }
+}
+
+/// Write end of the pipe the `SIGWINCH` handler notifies us through.
+static WINCH_FD: AtomicI32 = AtomicI32::new(-1);
+
+extern "C" fn on_sigwinch(_: c_int) {
+    let fd = WINCH_FD.load(Ordering::SeqCst);
+    if fd >= 0 {
+        unsafe { libc::write(fd, b"w".as_ptr() as *const _, 1) };
+    }
+}
+
+/// Turns `SIGWINCH` into readable data on a pipe so it can be polled. The
+/// previous signal handler is restored on drop.
+struct WinchPipe {
+    read: File,
+    write: File,
+    old: libc::sigaction,
+}
+
+impl WinchPipe {
+    fn new() -> io::Result<Self> {
+        let mut fds = [-1; 2];
+        if unsafe {
+            libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC | libc::O_NONBLOCK)
+        } < 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+        let read = unsafe { File::from_raw_fd(fds[0]) };
+        let write = unsafe { File::from_raw_fd(fds[1]) };
+        WINCH_FD.store(write.as_raw_fd(), Ordering::SeqCst);
+
+        let mut old: libc::sigaction = unsafe { std::mem::zeroed() };
+        let mut new: libc::sigaction = unsafe { std::mem::zeroed() };
+        new.sa_sigaction = on_sigwinch as extern "C" fn(c_int) as usize;
+        new.sa_flags = libc::SA_RESTART;
+        unsafe { libc::sigemptyset(&mut new.sa_mask) };
+        if unsafe { libc::sigaction(libc::SIGWINCH, &new, &mut old) } < 0 {
+            WINCH_FD.store(-1, Ordering::SeqCst);
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Self { read, write, old })
+    }
+
+    /// Consume all pending notifications.
+    fn drain(&self) {
+        let mut buf = [0u8; 64];
+        while read_fd(self.read.as_raw_fd(), &mut buf).unwrap_or(0) > 0 {}
+    }
+}
+
+impl Drop for WinchPipe {
+    fn drop(&mut self) {
+        unsafe {
+            libc::sigaction(libc::SIGWINCH, &self.old, std::ptr::null_mut())
+        };
+        let _ = WINCH_FD.compare_exchange(
+            self.write.as_raw_fd(),
+            -1,
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        );
+    }
+}
+
+fn read_fd(fd: RawFd, buf: &mut [u8]) -> io::Result<usize> {
+    loop {
+        let n =
+            unsafe { libc::read(fd, buf.as_mut_ptr() as *mut _, buf.len()) };
+        if n >= 0 {
+            return Ok(n as usize);
+        }
+        let err = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
+}
+
+fn write_all_fd(fd: RawFd, mut buf: &[u8]) -> io::Result<()> {
+    while !buf.is_empty() {
+        let n = unsafe { libc::write(fd, buf.as_ptr() as *const _, buf.len()) };
+        if n < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(err);
+        }
+        buf = &buf[n as usize..];
+    }
+    Ok(())
+}
+
+/// Actions triggered by the key following the escape character.
+enum Escape {
+    Detach,
+    Break,
+    Interrupt,
+    Help,
+    Send(Vec<u8>),
+}
+
+fn escape_action(escape: u8, key: u8) -> Escape {
+    match key {
+        b'q' => Escape::Detach,
+        b'b' => Escape::Break,
+        b'c' => Escape::Interrupt,
+        b'?' => Escape::Help,
+        k if k == escape => Escape::Send(vec![escape]),
+        k => Escape::Send(vec![escape, k]),
+    }
+}
+
+/// Forward input and output between the pty master `ptx` and the stdio file
+/// descriptors of `options`, see `Console::proxy`. This is also useful for
+/// ptys allocated through `Lxc::open_pty`.
+pub fn proxy_terminal<T: AsRawFd>(
+    ptx: &T,
+    options: &TerminalOptions,
+) -> Result<(), Error> {
+    let ptx = ptx.as_raw_fd();
+    let stdin = options.stdin_fd();
+    let stdout = options.stdout_fd();
+    // Anything but a control character disables the escape sequences.
+    let escape = match options.get_escape() {
+        e @ 1..=26 => e as u8,
+        _ => 0,
+    };
+    let escape_name = format!("^{}", (escape + b'a').saturating_sub(1) as char);
+
+    let interactive = tty::is_tty(stdin);
+    let _raw = if interactive {
+        Some(tty::RawMode::new(stdin)?)
+    } else {
+        None
+    };
+    let winch = if interactive {
+        tty::copy_winsize(stdin, ptx)?;
+        Some(WinchPipe::new()?)
+    } else {
+        None
+    };
+
+    let mut fds = [
+        libc::pollfd {
+            fd: stdin,
+            events: libc::POLLIN,
+            revents: 0,
+        },
+        libc::pollfd {
+            fd: ptx,
+            events: libc::POLLIN,
+            revents: 0,
+        },
+        libc::pollfd {
+            fd: winch.as_ref().map_or(-1, |w| w.read.as_raw_fd()),
+            events: libc::POLLIN,
+            revents: 0,
+        },
+    ];
+    let mut buf = [0u8; 4096];
+    let mut escaped = false;
+
+    loop {
+        let ret = unsafe {
+            libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, -1)
+        };
+        if ret < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            bail!("failed to poll terminal: {}", err);
+        }
+
+        if fds[2].revents & libc::POLLIN != 0 {
+            if let Some(winch) = &winch {
+                winch.drain();
+            }
+            // The tty may already be gone, which the next read will notice.
+            let _ = tty::copy_winsize(stdin, ptx);
+        }
+
+        if fds[1].revents & (libc::POLLIN | libc::POLLHUP | libc::POLLERR) != 0
+        {
+            match read_fd(ptx, &mut buf) {
+                // The container closed the tty.
+                Ok(0) => return Ok(()),
+                Err(ref e) if e.raw_os_error() == Some(libc::EIO) => {
+                    return Ok(())
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) => bail!("failed to read from terminal: {}", e),
+                Ok(n) => write_all_fd(stdout, &buf[..n])?,
+            }
+        }
+
+        if fds[0].revents & (libc::POLLIN | libc::POLLHUP | libc::POLLERR) != 0
+        {
+            let n = match read_fd(stdin, &mut buf) {
+                Ok(n) => n,
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => continue,
+                Err(e) => bail!("failed to read from stdin: {}", e),
+            };
+            if n == 0 {
+                // Nothing more to forward, but keep showing output.
+                fds[0].fd = -1;
+                continue;
+            }
+
+            let mut out = Vec::with_capacity(n);
+            for &c in &buf[..n] {
+                if !escaped {
+                    if escape != 0 && c == escape {
+                        escaped = true;
+                    } else {
+                        out.push(c);
+                    }
+                    continue;
+                }
+
+                escaped = false;
+                // Keep the order of input and the escape actions intact.
+                write_all_fd(ptx, &out)?;
+                out.clear();
+                match escape_action(escape, c) {
+                    Escape::Detach => return Ok(()),
+                    Escape::Break => unsafe {
+                        libc::tcsendbreak(ptx, 0);
+                    },
+                    Escape::Interrupt => unsafe {
+                        libc::ioctl(ptx, libc::TIOCSIG, libc::SIGINT);
+                    },
+                    Escape::Help => {
+                        let help = format!(
+                            "\r\n<{0} q> detach, <{0} b> send break, \
+                             <{0} c> send SIGINT, <{0} {0}> send {0}\r\n",
+                            escape_name
+                        );
+                        write_all_fd(stdout, help.as_bytes())?;
+                    }
+                    Escape::Send(data) => out.extend_from_slice(&data),
+                }
+            }
+            write_all_fd(ptx, &out)?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_action() {
+        assert!(matches!(escape_action(1, b'q'), Escape::Detach));
+        assert!(matches!(escape_action(1, b'b'), Escape::Break));
+        assert!(matches!(escape_action(1, b'c'), Escape::Interrupt));
+        assert!(matches!(escape_action(1, b'?'), Escape::Help));
+        assert!(matches!(escape_action(1, 1), Escape::Send(d) if d == [1]));
+        assert!(
+            matches!(escape_action(1, b'x'), Escape::Send(d) if d == [1, b'x'])
+        );
+    }
+}
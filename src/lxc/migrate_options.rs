@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: LGPL-2.1+
+
+use crate::util::ffi::ToCString;
+use std::ffi::{CString, NulError, OsStr};
+use std::os::raw::c_uint;
+use std::ptr;
+
+/// Actions which can be performed through `Lxc::migrate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrateCmd {
+    /// Dump the container's memory without stopping it, so that a later dump
+    /// only needs to transfer the pages changed in the meantime.
+    PreDump,
+    /// Checkpoint the container.
+    Dump,
+    /// Restore the container from a checkpoint.
+    Restore,
+    /// Check whether CRIU supports the features set via
+    /// `MigrateOptions::check_feature`.
+    FeatureCheck,
+}
+
+impl MigrateCmd {
+    pub(crate) fn raw(self) -> c_uint {
+        match self {
+            MigrateCmd::PreDump => lxc_sys::MIGRATE_PRE_DUMP as _,
+            MigrateCmd::Dump => lxc_sys::MIGRATE_DUMP as _,
+            MigrateCmd::Restore => lxc_sys::MIGRATE_RESTORE as _,
+            MigrateCmd::FeatureCheck => lxc_sys::MIGRATE_FEATURE_CHECK as _,
+        }
+    }
+}
+
+/// Type representing options for checkpointing and restoring a container.
+pub struct MigrateOptions {
+    raw: lxc_sys::migrate_opts,
+    directory: Option<CString>,
+    predump_dir: Option<CString>,
+    action_script: Option<CString>,
+    verbose: bool,
+    stop: bool,
+    ghost_limit: u64,
+}
+
+impl MigrateOptions {
+    pub fn new() -> Self {
+        Self {
+            raw: unsafe { std::mem::zeroed() },
+            directory: None,
+            predump_dir: None,
+            action_script: None,
+            verbose: false,
+            stop: false,
+            ghost_limit: 0,
+        }
+    }
+}
+
+impl Default for MigrateOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MigrateOptions {
+    /// Directory to write the checkpoint to or restore it from.
+    pub fn set_directory<T: AsRef<OsStr>>(
+        mut self,
+        v: T,
+    ) -> Result<Self, NulError> {
+        let cv = v.as_ref().to_c_string()?;
+
+        self.directory = Some(cv.into_owned());
+        Ok(self)
+    }
+
+    /// Directory of a previous pre-dump, relative to the checkpoint
+    /// directory. Only pages changed since then are dumped.
+    pub fn set_predump_dir<T: AsRef<OsStr>>(
+        mut self,
+        v: T,
+    ) -> Result<Self, NulError> {
+        let cv = v.as_ref().to_c_string()?;
+
+        self.predump_dir = Some(cv.into_owned());
+        Ok(self)
+    }
+
+    /// Script CRIU runs at the various stages of the checkpoint.
+    pub fn set_action_script<T: AsRef<OsStr>>(
+        mut self,
+        v: T,
+    ) -> Result<Self, NulError> {
+        let cv = v.as_ref().to_c_string()?;
+
+        self.action_script = Some(cv.into_owned());
+        Ok(self)
+    }
+
+    pub fn set_verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// Stop the container after it has been dumped.
+    pub fn set_stop(mut self, stop: bool) -> Self {
+        self.stop = stop;
+        self
+    }
+
+    /// Maximum size of deleted files CRIU includes in the checkpoint. Pass
+    /// `0` to use CRIU's default.
+    pub fn set_ghost_limit(mut self, v: u64) -> Self {
+        self.ghost_limit = v;
+        self
+    }
+
+    pub fn check_feature(mut self, feature: u64, on: bool) -> Self {
+        if on {
+            self.raw.features_to_check |= feature;
+        } else {
+            self.raw.features_to_check &= !feature;
+        }
+        self
+    }
+
+    /// Check for CRIU's memory tracking support, required for pre-dumps.
+    pub fn mem_track(self, on: bool) -> Self {
+        self.check_feature(lxc_sys::FEATURE_MEM_TRACK as _, on)
+    }
+
+    /// Check for CRIU's lazy pages support.
+    pub fn lazy_pages(self, on: bool) -> Self {
+        self.check_feature(lxc_sys::FEATURE_LAZY_PAGES as _, on)
+    }
+
+    /// The features to check. liblxc updates this during
+    /// `MigrateCmd::FeatureCheck`.
+    pub fn features_to_check(&self) -> u64 {
+        self.raw.features_to_check
+    }
+
+    fn finish(&mut self) {
+        self.raw.directory = self
+            .directory
+            .as_ref()
+            .map(|s| s.as_ptr() as *mut _)
+            .unwrap_or(ptr::null_mut());
+        self.raw.predump_dir = self
+            .predump_dir
+            .as_ref()
+            .map(|s| s.as_ptr() as *mut _)
+            .unwrap_or(ptr::null_mut());
+        self.raw.action_script = self
+            .action_script
+            .as_ref()
+            .map(|s| s.as_ptr() as *mut _)
+            .unwrap_or(ptr::null_mut());
+        self.raw.verbose = self.verbose;
+        self.raw.stop = self.stop;
+        self.raw.ghost_limit = self.ghost_limit;
+    }
+
+    pub fn raw(&mut self) -> &mut lxc_sys::migrate_opts {
+        self.finish();
+        &mut self.raw
+    }
+}
@@ -5,17 +5,47 @@
 
 use anyhow::{bail, Error};
 use std::ffi::{CStr, CString, OsStr};
-use std::os::raw::{c_char, c_int};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::mem::ManuallyDrop;
+use std::os::raw::{c_char, c_int, c_uint, c_ulong, c_void};
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::os::unix::process::ExitStatusExt;
+use std::panic;
 use std::path::Path;
+use std::process::{ExitStatus, Output};
 use std::ptr;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use crate::util::ffi::{StringArrayIter, ToCString};
+use crate::util::ffi::{read_sized_string, StringArrayIter, ToCString};
+use crate::util::tty;
 
 mod attach_options;
+mod bdev_specs;
+mod clone_options;
+mod command;
+mod config;
+mod console;
 mod log_options;
+mod migrate_options;
+mod seccomp;
+mod snapshot;
+mod state;
+mod terminal_options;
 pub use attach_options::AttachOptions;
+pub use bdev_specs::BdevSpecs;
+pub use clone_options::CloneOptions;
+pub use command::{ContainerChild, ContainerCommand, Stdio};
+pub use config::Config;
+pub use console::{proxy_terminal, Console};
 pub use log_options::LogOptions;
+pub use migrate_options::{MigrateCmd, MigrateOptions};
+pub use seccomp::{
+    SeccompRequest, SeccompResponse, SeccompSupervisor, AUDIT_ARCH_NATIVE,
+};
+pub use snapshot::Snapshot;
+pub use state::State;
+pub use terminal_options::TerminalOptions;
 
 /// The main container handle. This implements the methods for `struct
 /// lxc_container`.
@@ -77,6 +107,89 @@ pub fn get_default_path() -> &'static str {
     }
 }
 
+/// Returns the states `Lxc::wait` can wait for.
+pub fn get_wait_states() -> Vec<State> {
+    let nr = unsafe { lxc_sys::lxc_get_wait_states(ptr::null_mut()) };
+    if nr <= 0 {
+        return Vec::new();
+    }
+
+    let mut states: Vec<*const c_char> = vec![ptr::null(); nr as usize];
+    unsafe { lxc_sys::lxc_get_wait_states(states.as_mut_ptr()) };
+    states
+        .into_iter()
+        .filter(|s| !s.is_null())
+        .filter_map(|s| unsafe { CStr::from_ptr(s) }.to_str().ok())
+        .filter_map(|s| s.parse().ok())
+        .collect()
+}
+
+/// Convert an optional timeout into the seconds liblxc expects, where `-1`
+/// means to wait forever.
+fn timeout_to_c_int(timeout: Option<Duration>) -> Result<c_int, Error> {
+    match timeout {
+        Some(to) => {
+            let secs = to.as_secs();
+            // seconds can be large...
+            if secs > c_int::MAX as u64 {
+                bail!("timeout too large");
+            }
+            Ok(secs as _)
+        }
+        None => Ok(-1),
+    }
+}
+
+/// Program to run through `exec_with_ctty`.
+struct ExecPayload {
+    program: *const c_char,
+    argv: *const *const c_char,
+}
+
+/// Runs in the attached process: make its standard input the controlling
+/// terminal of a new session and exec the program.
+extern "C" fn exec_with_ctty(payload: *mut c_void) -> c_int {
+    let payload = unsafe { &*(payload as *const ExecPayload) };
+    unsafe {
+        libc::setsid();
+        if libc::ioctl(0, libc::TIOCSCTTY, 0) < 0 {
+            return -1;
+        }
+        libc::execvp(payload.program, payload.argv);
+    }
+    -1
+}
+
+/// Closure to run through `run_function` and the pipe to write its result to.
+struct FunctionPayload<F> {
+    func: Option<F>,
+    fd: RawFd,
+}
+
+/// Runs in the attached process: call the closure and send back its result.
+/// The return value becomes the exit code of the attached process.
+extern "C" fn run_function<F>(payload: *mut c_void) -> c_int
+where
+    F: FnOnce() -> Vec<u8>,
+{
+    let payload = unsafe { &mut *(payload as *mut FunctionPayload<F>) };
+    let func = match payload.func.take() {
+        Some(func) => func,
+        None => return 1,
+    };
+    // Unwinding out of an extern "C" function aborts.
+    let data = match panic::catch_unwind(panic::AssertUnwindSafe(func)) {
+        Ok(data) => data,
+        Err(_) => return 1,
+    };
+
+    let mut out = unsafe { ManuallyDrop::new(File::from_raw_fd(payload.fd)) };
+    match out.write_all(&data) {
+        Ok(()) => 0,
+        Err(_) => 1,
+    }
+}
+
 pub fn set_log(options: &mut LogOptions) -> Result<(), Error> {
     let ret = unsafe { lxc_sys::lxc_log_init(options.raw()) };
 
@@ -123,6 +236,238 @@ impl Lxc {
         }
     }
 
+    /// Access the container's configuration.
+    pub fn config(&self) -> Config<'_> {
+        Config::new(self)
+    }
+
+    /// Determine if the container is defined, i.e. if a configuration file
+    /// for it exists.
+    pub fn is_defined(&self) -> bool {
+        unsafe { (*self.handle).is_defined.unwrap()(self.handle) }
+    }
+
+    /// Create the container using the given `template`. The container's
+    /// storage is created with the backing store `bdevtype` (`None` selects
+    /// liblxc's default) configured through `specs`. The `argv` arguments
+    /// are passed on to the template.
+    pub fn create<T: AsRef<OsStr>>(
+        &self,
+        template: T,
+        bdevtype: Option<&str>,
+        specs: Option<&mut BdevSpecs>,
+        quiet: bool,
+        argv: Vec<&OsStr>,
+    ) -> Result<(), Error> {
+        let ctemplate = template.as_ref().to_c_string()?;
+        let cbdevtype = match bdevtype {
+            Some(t) => Some(t.to_c_string()?),
+            None => None,
+        };
+        let flags = if quiet {
+            lxc_sys::LXC_CREATE_QUIET as c_int
+        } else {
+            0
+        };
+
+        let cargv: Vec<_> =
+            argv.iter().map(|arg| arg.to_c_string().unwrap()).collect();
+        let mut args: Vec<_> = cargv.iter().map(|arg| arg.as_ptr()).collect();
+        args.push(std::ptr::null());
+
+        let created = unsafe {
+            // LXC doesn't alter char *const argv[] so the cast is safe.
+            (*self.handle).create.unwrap()(
+                self.handle,
+                ctemplate.as_ptr(),
+                cbdevtype.as_ref().map_or(ptr::null(), |t| t.as_ptr()),
+                specs.map_or(ptr::null_mut(), |s| s.raw() as *mut _),
+                flags,
+                args.as_ptr() as *const *mut c_char,
+            )
+        };
+        if !created {
+            bail!("failed to create container");
+        }
+        Ok(())
+    }
+
+    /// Destroy the container. This fails if the container has snapshots.
+    pub fn destroy(&self) -> Result<(), Error> {
+        let destroyed = unsafe { (*self.handle).destroy.unwrap()(self.handle) };
+        if !destroyed {
+            bail!("failed to destroy container");
+        }
+        Ok(())
+    }
+
+    /// Destroy the container and all of its snapshots.
+    pub fn destroy_with_snapshots(&self) -> Result<(), Error> {
+        let destroyed = unsafe {
+            (*self.handle).destroy_with_snapshots.unwrap()(self.handle)
+        };
+        if !destroyed {
+            bail!("failed to destroy container and its snapshots");
+        }
+        Ok(())
+    }
+
+    /// Clone the container into a new container called `newname`. This is a
+    /// wrapper for liblxc's `clone` function. Returns a handle for the new
+    /// container.
+    pub fn copy<S: AsRef<OsStr>>(
+        &self,
+        newname: S,
+        options: &CloneOptions,
+    ) -> Result<Lxc, Error> {
+        let cname = newname.as_ref().to_c_string()?;
+        let handle = unsafe {
+            (*self.handle).clone.unwrap()(
+                self.handle,
+                cname.as_ptr(),
+                options.lxcpath(),
+                options.flags(),
+                options.bdevtype(),
+                ptr::null(),
+                options.newsize(),
+                ptr::null_mut(),
+            )
+        };
+
+        if handle.is_null() {
+            bail!("failed to clone container");
+        }
+
+        Ok(Lxc { handle })
+    }
+
+    /// Rename the container to `newname`. The container must be stopped and
+    /// `newname` must not be used by another container in the same path.
+    pub fn rename(&self, newname: &str) -> Result<(), Error> {
+        if self.is_running() {
+            bail!("container must be stopped to be renamed");
+        }
+
+        let path = match self.path() {
+            Some(p) => p,
+            None => bail!("failed to determine container path"),
+        };
+        if Lxc::new(newname, path)?.is_defined() {
+            bail!("container {} already exists", newname);
+        }
+
+        let cname = newname.to_c_string()?;
+        let renamed = unsafe {
+            (*self.handle).rename.unwrap()(self.handle, cname.as_ptr())
+        };
+        if !renamed {
+            bail!("failed to rename container");
+        }
+        Ok(())
+    }
+
+    /// Create a snapshot of the container. If `commentfile` is given its
+    /// contents are stored as the snapshot's comment. Returns the name of the
+    /// new snapshot.
+    pub fn snapshot(
+        &self,
+        commentfile: Option<&Path>,
+    ) -> Result<String, Error> {
+        let ccomment = match commentfile {
+            Some(p) => Some(p.to_c_string()?),
+            None => None,
+        };
+        let nr = unsafe {
+            (*self.handle).snapshot.unwrap()(
+                self.handle,
+                ccomment.as_ref().map_or(ptr::null(), |c| c.as_ptr()),
+            )
+        };
+        if nr < 0 {
+            bail!("failed to create snapshot");
+        }
+        Ok(format!("snap{}", nr))
+    }
+
+    /// Get all snapshots of the container.
+    pub fn snapshot_list(&self) -> Result<Vec<Snapshot>, Error> {
+        let mut snapshots: *mut lxc_sys::lxc_snapshot = ptr::null_mut();
+
+        let nr = unsafe {
+            (*self.handle).snapshot_list.unwrap()(self.handle, &mut snapshots)
+        };
+        if nr < 0 {
+            bail!("failed to list snapshots");
+        }
+        if snapshots.is_null() {
+            return Ok(Vec::new());
+        }
+
+        let mut list = Vec::with_capacity(nr as usize);
+        unsafe {
+            for i in 0..nr as usize {
+                let snapshot = snapshots.add(i);
+                list.push(Snapshot::from_raw(&*snapshot));
+                if let Some(free) = (*snapshot).free {
+                    free(snapshot);
+                }
+            }
+            libc::free(snapshots as *mut _);
+        }
+        Ok(list)
+    }
+
+    /// Restore the snapshot `snapname` into the container `newname`. If
+    /// `newname` is `None` the container itself is replaced by the snapshot.
+    pub fn snapshot_restore(
+        &self,
+        snapname: &str,
+        newname: Option<&str>,
+    ) -> Result<(), Error> {
+        let csnapname = snapname.to_c_string()?;
+        let cnewname = match newname {
+            Some(n) => Some(n.to_c_string()?),
+            None => None,
+        };
+        let restored = unsafe {
+            (*self.handle).snapshot_restore.unwrap()(
+                self.handle,
+                csnapname.as_ptr(),
+                cnewname.as_ref().map_or(ptr::null(), |n| n.as_ptr()),
+            )
+        };
+        if !restored {
+            bail!("failed to restore snapshot {}", snapname);
+        }
+        Ok(())
+    }
+
+    /// Destroy the snapshot `snapname`.
+    pub fn snapshot_destroy(&self, snapname: &str) -> Result<(), Error> {
+        let csnapname = snapname.to_c_string()?;
+        let destroyed = unsafe {
+            (*self.handle).snapshot_destroy.unwrap()(
+                self.handle,
+                csnapname.as_ptr(),
+            )
+        };
+        if !destroyed {
+            bail!("failed to destroy snapshot {}", snapname);
+        }
+        Ok(())
+    }
+
+    /// Destroy all snapshots of the container.
+    pub fn snapshot_destroy_all(&self) -> Result<(), Error> {
+        let destroyed = unsafe {
+            (*self.handle).snapshot_destroy_all.unwrap()(self.handle)
+        };
+        if !destroyed {
+            bail!("failed to destroy snapshots");
+        }
+        Ok(())
+    }
+
     /// Attempt to start the container. If `stub` is true, the container's
     /// `lxc.execute.cmd` is executed instead of `lxc.init.cmd`.
     pub fn start(&self, stub: bool, argv: Vec<&OsStr>) -> Result<(), Error> {
@@ -155,17 +500,7 @@ impl Lxc {
 
     /// Atetmpt to shutdown a container with a timeout.
     pub fn shutdown(&self, timeout: Option<Duration>) -> Result<(), Error> {
-        let timeout: c_int = match timeout {
-            Some(to) => {
-                let secs = to.as_secs();
-                // seconds can be large...
-                if secs > (!(0 as c_int)) as u64 {
-                    bail!("timeout too large");
-                }
-                secs as _
-            }
-            None => -1,
-        };
+        let timeout = timeout_to_c_int(timeout)?;
         let down =
             unsafe { (*self.handle).shutdown.unwrap()(self.handle, timeout) };
         if !down {
@@ -174,6 +509,31 @@ impl Lxc {
         Ok(())
     }
 
+    /// Request the container to reboot. This does not wait for the reboot to
+    /// complete.
+    pub fn reboot(&self) -> Result<(), Error> {
+        let rebooted = unsafe { (*self.handle).reboot.unwrap()(self.handle) };
+        if !rebooted {
+            bail!("failed to reboot container");
+        }
+        Ok(())
+    }
+
+    /// Reboot the container and wait for it to come back up within
+    /// `timeout`. Pass `None` to wait forever.
+    pub fn reboot_with_timeout(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<(), Error> {
+        let timeout = timeout_to_c_int(timeout)?;
+        let rebooted =
+            unsafe { (*self.handle).reboot2.unwrap()(self.handle, timeout) };
+        if !rebooted {
+            bail!("failed to reboot container");
+        }
+        Ok(())
+    }
+
     /// Attempt to stop a running container.
     pub fn stop(&self) -> Result<(), Error> {
         let stopped = unsafe { (*self.handle).stop.unwrap()(self.handle) };
@@ -183,6 +543,34 @@ impl Lxc {
         Ok(())
     }
 
+    /// Wait for the container to reach `state`. Pass `None` to wait forever.
+    pub fn wait(
+        &self,
+        state: State,
+        timeout: Option<Duration>,
+    ) -> Result<(), Error> {
+        let cstate = state.as_str().to_c_string()?;
+        let ctimeout = timeout_to_c_int(timeout)?;
+        let start = Instant::now();
+        let reached = unsafe {
+            (*self.handle).wait.unwrap()(self.handle, cstate.as_ptr(), ctimeout)
+        };
+        if !reached {
+            // liblxc also fails for containers it can't monitor.
+            match timeout {
+                Some(to) if start.elapsed() >= to => bail!(
+                    "timed out waiting for container to reach state {}",
+                    state
+                ),
+                _ => bail!(
+                    "failed to wait for container to reach state {}",
+                    state
+                ),
+            }
+        }
+        Ok(())
+    }
+
     /// Determine if the caller may control the container.
     pub fn may_control(&self) -> Result<(), Error> {
         if !unsafe { (*self.handle).may_control.unwrap()(self.handle) } {
@@ -199,6 +587,59 @@ impl Lxc {
         unsafe { (*self.handle).is_running.unwrap()(self.handle) }
     }
 
+    /// The pid of the container's init process as seen from the caller.
+    pub fn init_pid(&self) -> Result<libc::pid_t, Error> {
+        let pid = unsafe { (*self.handle).init_pid.unwrap()(self.handle) };
+        if pid < 0 {
+            bail!("failed to retrieve pid of container init");
+        }
+        Ok(pid)
+    }
+
+    /// A pidfd referring to the container's init process. Unlike the pid this
+    /// cannot be recycled while it is open.
+    pub fn init_pidfd(&self) -> Result<File, Error> {
+        let fd = unsafe { (*self.handle).init_pidfd.unwrap()(self.handle) };
+        if fd < 0 {
+            bail!("failed to retrieve pidfd of container init");
+        }
+        Ok(unsafe { File::from_raw_fd(fd) })
+    }
+
+    /// Send `signal` to the container's init process. This goes through a
+    /// pidfd if liblxc and the kernel support it and falls back to the pid
+    /// otherwise.
+    pub fn kill(&self, signal: c_int) -> Result<(), Error> {
+        if let Ok(pidfd) = self.init_pidfd() {
+            let ret = unsafe {
+                libc::syscall(
+                    libc::SYS_pidfd_send_signal,
+                    pidfd.as_raw_fd(),
+                    signal,
+                    ptr::null::<libc::siginfo_t>(),
+                    0,
+                )
+            };
+            if ret == 0 {
+                return Ok(());
+            }
+            let err = std::io::Error::last_os_error();
+            if err.raw_os_error() != Some(libc::ENOSYS) {
+                bail!("failed to send signal {}: {}", signal, err);
+            }
+        }
+
+        let pid = self.init_pid()?;
+        if unsafe { libc::kill(pid, signal) } < 0 {
+            bail!(
+                "failed to send signal {}: {}",
+                signal,
+                std::io::Error::last_os_error()
+            );
+        }
+        Ok(())
+    }
+
     /// Try to run a program inside the container.
     pub fn attach_run_wait<T: AsRef<OsStr>>(
         &self,
@@ -225,12 +666,194 @@ impl Lxc {
         }
     }
 
+    /// Like `attach_run_wait`, but collect the standard output and error of
+    /// the program instead of passing them through. Standard input is
+    /// `/dev/null`.
+    pub fn attach_output<T: AsRef<OsStr>>(
+        &self,
+        options: &mut AttachOptions,
+        program: T,
+        argv: Vec<&OsStr>,
+    ) -> Result<Output, Error> {
+        command::spawn(
+            self,
+            options,
+            program.as_ref(),
+            argv,
+            [Stdio::Null, Stdio::Piped, Stdio::Piped],
+        )?
+        .wait_with_output()
+    }
+
+    /// Run `func` in a process attached to the container, i.e. within its
+    /// namespaces, cgroup and LSM context, and return the data it returns.
+    /// This needs no binary in the container. `func` runs in a forked copy of
+    /// the caller, so changes it makes to memory are not visible to the
+    /// caller.
+    ///
+    /// Only the calling thread exists in the forked copy and locks held by
+    /// other threads (e.g. the allocator's) stay locked forever there. As
+    /// `func` returns an allocated `Vec`, this must not be called from a
+    /// multithreaded process. Panics in `func` are caught and make the
+    /// attached process exit with an error, which is returned here.
+    pub fn attach_function<F>(
+        &self,
+        options: &mut AttachOptions,
+        func: F,
+    ) -> Result<Vec<u8>, Error>
+    where
+        F: FnOnce() -> Vec<u8>,
+    {
+        let mut fds = [-1; 2];
+        if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } < 0 {
+            bail!("failed to create pipe: {}", std::io::Error::last_os_error());
+        }
+        let mut read = unsafe { File::from_raw_fd(fds[0]) };
+        let write = unsafe { File::from_raw_fd(fds[1]) };
+        let mut payload = FunctionPayload {
+            func: Some(func),
+            fd: write.as_raw_fd(),
+        };
+
+        let pid = unsafe {
+            self.attach_raw(
+                options,
+                Some(run_function::<F>),
+                &mut payload as *mut FunctionPayload<F> as *mut _,
+                None,
+            )
+        };
+        // Only the attached process may keep the pipe open, so reading ends
+        // when it exits.
+        drop(write);
+        let pid = pid?;
+
+        let mut data = Vec::new();
+        let read_result = read.read_to_end(&mut data);
+
+        let mut status = 0;
+        while unsafe { libc::waitpid(pid, &mut status, 0) } < 0 {
+            let err = std::io::Error::last_os_error();
+            if err.kind() != std::io::ErrorKind::Interrupted {
+                bail!("failed to wait for {}: {}", pid, err);
+            }
+        }
+        read_result?;
+
+        let status = ExitStatus::from_raw(status);
+        if !status.success() {
+            bail!("function failed in container: {}", status);
+        }
+        Ok(data)
+    }
+
+    /// Build a process to run in the container. Unlike `attach_run_wait` it
+    /// can be spawned without waiting for it to exit.
+    pub fn command<T: AsRef<OsStr>>(&self, program: T) -> ContainerCommand<'_> {
+        ContainerCommand::new(self, program)
+    }
+
+    /// Like `attach_run_wait`, but run the program with `pty` as its
+    /// controlling terminal and standard input, output and error, without
+    /// waiting for it. `pty` is usually the slave side of a pty allocated
+    /// through `open_pty`. Returns the pid of the program.
+    pub fn attach_pty<T: AsRef<OsStr>>(
+        &self,
+        options: &mut AttachOptions,
+        pty: File,
+        program: T,
+        argv: Vec<&OsStr>,
+    ) -> Result<libc::pid_t, Error> {
+        let cprogram = program.as_ref().to_c_string()?;
+        let cargv = argv
+            .iter()
+            .map(|arg| arg.to_c_string())
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut args: Vec<_> = cargv.iter().map(|arg| arg.as_ptr()).collect();
+        args.push(ptr::null());
+        let mut payload = ExecPayload {
+            program: cprogram.as_ptr(),
+            argv: args.as_ptr(),
+        };
+
+        let fd = pty.as_raw_fd();
+        unsafe {
+            self.attach_raw(
+                options,
+                Some(exec_with_ctty),
+                &mut payload as *mut ExecPayload as *mut _,
+                Some([fd, fd, fd]),
+            )
+        }
+    }
+
+    /// Run `func` with `payload` in a process attached to the container and
+    /// return its pid. No terminal is allocated and, if given, `stdio` are
+    /// used as its standard input, output and error. `options` are left
+    /// untouched.
+    ///
+    /// # Safety
+    ///
+    /// `payload` must be what `func` expects and valid for this call.
+    unsafe fn attach_raw(
+        &self,
+        options: &mut AttachOptions,
+        func: lxc_sys::lxc_attach_exec_t,
+        payload: *mut c_void,
+        stdio: Option<[RawFd; 3]>,
+    ) -> Result<libc::pid_t, Error> {
+        // The caller's options must not refer to our fds.
+        let mut raw = *options.raw();
+        raw.attach_flags &= !(lxc_sys::LXC_ATTACH_TERMINAL as c_int);
+        if let Some([stdin, stdout, stderr]) = stdio {
+            raw.stdin_fd = stdin;
+            raw.stdout_fd = stdout;
+            raw.stderr_fd = stderr;
+        }
+
+        let mut pid: libc::pid_t = -1;
+        let ret = (*self.handle).attach.unwrap()(
+            self.handle,
+            func,
+            payload,
+            &mut raw,
+            &mut pid,
+        );
+        if ret < 0 {
+            bail!("failed to attach to container");
+        }
+        Ok(pid)
+    }
+
+    /// Open the devpts instance of the container.
+    pub fn devpts_fd(&self) -> Result<File, Error> {
+        let fd = unsafe { (*self.handle).devpts_fd.unwrap()(self.handle) };
+        if fd < 0 {
+            bail!("failed to retrieve devpts fd");
+        }
+        Ok(unsafe { File::from_raw_fd(fd) })
+    }
+
+    /// Allocate a pty from the container's devpts instance, so it is owned
+    /// by the container instead of the host. Returns the master and the
+    /// slave side.
+    pub fn open_pty(&self) -> Result<(File, File), Error> {
+        let devpts = self.devpts_fd()?;
+        match tty::open_pty(devpts.as_raw_fd()) {
+            Ok(pty) => Ok(pty),
+            Err(err) => bail!("failed to allocate pty: {}", err),
+        }
+    }
+
     /// Determine state of container.
-    pub fn state(&self) -> &'static str {
+    pub fn state(&self) -> State {
         let cstr: &CStr = unsafe {
             CStr::from_ptr((*self.handle).state.unwrap()(self.handle))
         };
-        cstr.to_str().unwrap_or("UNKNOWN")
+        cstr.to_str()
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(State::Unknown)
     }
 
     /// Get network interfaces of container.
@@ -310,15 +933,246 @@ impl Lxc {
         unsafe { StringArrayIter::new(addresses, len) }
     }
 
+    /// Get the value of the cgroup file `key` (e.g. `memory.current`) of a
+    /// running container.
+    pub fn get_cgroup_item(&self, key: &str) -> Result<String, Error> {
+        let ckey = key.to_c_string()?;
+        match read_sized_string(|retv, inlen| unsafe {
+            (*self.handle).get_cgroup_item.unwrap()(
+                self.handle,
+                ckey.as_ptr(),
+                retv,
+                inlen,
+            )
+        }) {
+            Some(value) => Ok(value),
+            None => bail!("failed to get cgroup item {}", key),
+        }
+    }
+
+    /// Write `value` to the cgroup file `key` (e.g. `pids.max`) of a running
+    /// container.
+    pub fn set_cgroup_item(&self, key: &str, value: &str) -> Result<(), Error> {
+        let ckey = key.to_c_string()?;
+        let cvalue = value.to_c_string()?;
+        let set = unsafe {
+            (*self.handle).set_cgroup_item.unwrap()(
+                self.handle,
+                ckey.as_ptr(),
+                cvalue.as_ptr(),
+            )
+        };
+        if !set {
+            bail!("failed to set cgroup item {}", key);
+        }
+        Ok(())
+    }
+
+    /// Checkpoint or restore the container using CRIU. This is a wrapper for
+    /// liblxc's `migrate` function.
+    pub fn migrate(
+        &self,
+        cmd: MigrateCmd,
+        options: &mut MigrateOptions,
+    ) -> Result<(), Error> {
+        let ret = unsafe {
+            (*self.handle).migrate.unwrap()(
+                self.handle,
+                cmd.raw(),
+                options.raw(),
+                std::mem::size_of::<lxc_sys::migrate_opts>() as c_uint,
+            )
+        };
+        if ret != 0 {
+            match cmd {
+                MigrateCmd::PreDump => bail!("failed to pre-dump container"),
+                MigrateCmd::Dump => bail!("failed to checkpoint container"),
+                MigrateCmd::Restore => bail!("failed to restore container"),
+                MigrateCmd::FeatureCheck => {
+                    bail!("CRIU does not support the requested features")
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Make the device node `src_path` available in the running container at
+    /// `dest_path`, or at the same path if `dest_path` is `None`.
+    pub fn add_device_node(
+        &self,
+        src_path: &Path,
+        dest_path: Option<&Path>,
+    ) -> Result<(), Error> {
+        let csrc = src_path.to_c_string()?;
+        let cdest = match dest_path {
+            Some(p) => Some(p.to_c_string()?),
+            None => None,
+        };
+        let added = unsafe {
+            (*self.handle).add_device_node.unwrap()(
+                self.handle,
+                csrc.as_ptr(),
+                cdest.as_ref().map_or(ptr::null(), |d| d.as_ptr()),
+            )
+        };
+        if !added {
+            bail!("failed to add device node {:?}", src_path);
+        }
+        Ok(())
+    }
+
+    /// Remove the device node `src_path` from the running container, which
+    /// was added at `dest_path` or at the same path if `dest_path` is `None`.
+    pub fn remove_device_node(
+        &self,
+        src_path: &Path,
+        dest_path: Option<&Path>,
+    ) -> Result<(), Error> {
+        let csrc = src_path.to_c_string()?;
+        let cdest = match dest_path {
+            Some(p) => Some(p.to_c_string()?),
+            None => None,
+        };
+        let removed = unsafe {
+            (*self.handle).remove_device_node.unwrap()(
+                self.handle,
+                csrc.as_ptr(),
+                cdest.as_ref().map_or(ptr::null(), |d| d.as_ptr()),
+            )
+        };
+        if !removed {
+            bail!("failed to remove device node {:?}", src_path);
+        }
+        Ok(())
+    }
+
+    /// Move the host network interface `dev` into the running container,
+    /// renaming it to `dst_dev` if given.
+    pub fn attach_interface(
+        &self,
+        dev: &str,
+        dst_dev: Option<&str>,
+    ) -> Result<(), Error> {
+        let cdev = dev.to_c_string()?;
+        let cdst = match dst_dev {
+            Some(d) => Some(d.to_c_string()?),
+            None => None,
+        };
+        let attached = unsafe {
+            (*self.handle).attach_interface.unwrap()(
+                self.handle,
+                cdev.as_ptr(),
+                cdst.as_ref().map_or(ptr::null(), |d| d.as_ptr()),
+            )
+        };
+        if !attached {
+            bail!("failed to attach interface {}", dev);
+        }
+        Ok(())
+    }
+
+    /// Move the network interface `dev` out of the running container back to
+    /// the host, renaming it to `dst_dev` if given.
+    pub fn detach_interface(
+        &self,
+        dev: &str,
+        dst_dev: Option<&str>,
+    ) -> Result<(), Error> {
+        let cdev = dev.to_c_string()?;
+        let cdst = match dst_dev {
+            Some(d) => Some(d.to_c_string()?),
+            None => None,
+        };
+        let detached = unsafe {
+            (*self.handle).detach_interface.unwrap()(
+                self.handle,
+                cdev.as_ptr(),
+                cdst.as_ref().map_or(ptr::null(), |d| d.as_ptr()),
+            )
+        };
+        if !detached {
+            bail!("failed to detach interface {}", dev);
+        }
+        Ok(())
+    }
+
+    /// Mount `source` at `target` inside the running container. `fstype`
+    /// and `flags` are passed on to `mount(2)`. This requires the container
+    /// to be started with `lxc.mount.auto = shmounts:<path>`.
+    pub fn mount(
+        &self,
+        source: &Path,
+        target: &Path,
+        fstype: Option<&str>,
+        flags: c_ulong,
+    ) -> Result<(), Error> {
+        let csource = source.to_c_string()?;
+        let ctarget = target.to_c_string()?;
+        let cfstype = match fstype {
+            Some(t) => Some(t.to_c_string()?),
+            None => None,
+        };
+        let mut mnt = lxc_sys::lxc_mount {
+            version: lxc_sys::LXC_MOUNT_API_V1 as c_int,
+        };
+        let ret = unsafe {
+            (*self.handle).mount.unwrap()(
+                self.handle,
+                csource.as_ptr(),
+                ctarget.as_ptr(),
+                cfstype.as_ref().map_or(ptr::null(), |t| t.as_ptr()),
+                flags,
+                ptr::null(),
+                &mut mnt,
+            )
+        };
+        if ret < 0 {
+            bail!("failed to mount {:?} at {:?}", source, target);
+        }
+        Ok(())
+    }
+
+    /// Unmount `target` inside the running container. `flags` are passed on
+    /// to `umount2(2)`.
+    pub fn umount(&self, target: &Path, flags: c_ulong) -> Result<(), Error> {
+        let ctarget = target.to_c_string()?;
+        let mut mnt = lxc_sys::lxc_mount {
+            version: lxc_sys::LXC_MOUNT_API_V1 as c_int,
+        };
+        let ret = unsafe {
+            (*self.handle).umount.unwrap()(
+                self.handle,
+                ctarget.as_ptr(),
+                flags,
+                &mut mnt,
+            )
+        };
+        if ret < 0 {
+            bail!("failed to unmount {:?}", target);
+        }
+        Ok(())
+    }
+
     pub fn daemonize(&self, daemonize: bool) {
         unsafe {
             (*self.handle).want_daemonize.unwrap()(self.handle, daemonize)
         };
     }
 
-    pub fn terminal(&self) -> Result<(), Error> {
+    /// Attach to a terminal of the container using liblxc's own proxy. This
+    /// blocks until the user detaches using the escape sequence. See
+    /// `console_getfd` for an alternative which doesn't block and propagates
+    /// window size changes.
+    pub fn terminal(&self, options: &TerminalOptions) -> Result<(), Error> {
         let ret = unsafe {
-            (*self.handle).console.unwrap()(self.handle, 0, 0, 1, 2, 1)
+            (*self.handle).console.unwrap()(
+                self.handle,
+                options.get_ttynum(),
+                options.stdin_fd(),
+                options.stdout_fd(),
+                options.stderr_fd(),
+                options.get_escape(),
+            )
         };
 
         if ret < 0 {
@@ -327,6 +1181,96 @@ impl Lxc {
         Ok(())
     }
 
+    /// Allocate a tty of the container, `ttynum` as in
+    /// `TerminalOptions::ttynum`. Unlike `terminal` this does not block, the
+    /// returned `Console` gives access to the pty master and can proxy it.
+    pub fn console_getfd(&self, ttynum: c_int) -> Result<Console, Error> {
+        let mut ttynum = ttynum;
+        let mut ptxfd: c_int = -1;
+        let ttyfd = unsafe {
+            (*self.handle).console_getfd.unwrap()(
+                self.handle,
+                &mut ttynum,
+                &mut ptxfd,
+            )
+        };
+
+        if ttyfd < 0 {
+            bail!("failed to allocate tty");
+        }
+        Ok(unsafe { Console::from_raw(ttynum, ttyfd, ptxfd) })
+    }
+
+    /// The seccomp notification fd of a container started by this process,
+    /// or `None` if its seccomp profile does not use the `notify` action. The
+    /// fd stays owned by the container handle.
+    pub fn seccomp_notify_fd(&self) -> Option<RawFd> {
+        let fd =
+            unsafe { (*self.handle).seccomp_notify_fd.unwrap()(self.handle) };
+        if fd < 0 {
+            None
+        } else {
+            Some(fd)
+        }
+    }
+
+    /// Retrieve the seccomp notification fd of a running container, e.g. to
+    /// pass it to a `SeccompSupervisor`.
+    pub fn seccomp_notify_fd_active(&self) -> Result<File, Error> {
+        let fd = unsafe {
+            (*self.handle).seccomp_notify_fd_active.unwrap()(self.handle)
+        };
+
+        if fd < 0 {
+            bail!("failed to retrieve seccomp notification fd");
+        }
+        Ok(unsafe { File::from_raw_fd(fd) })
+    }
+
+    /// Retrieve the contents of the container's console ring buffer (see
+    /// `lxc.console.buffer.size`). If `read` is set at most `read_max` bytes
+    /// are returned, or the whole buffer if `read_max` is `None`. If `clear`
+    /// is set the buffer is cleared afterwards.
+    pub fn console_log(
+        &self,
+        read: bool,
+        clear: bool,
+        read_max: Option<u64>,
+    ) -> Result<Vec<u8>, Error> {
+        let mut len: u64 = read_max.unwrap_or(0);
+        let mut log = lxc_sys::lxc_console_log {
+            clear,
+            read,
+            read_max: &mut len,
+            data: ptr::null_mut(),
+        };
+
+        let ret = unsafe {
+            (*self.handle).console_log.unwrap()(self.handle, &mut log)
+        };
+        if ret == -libc::ENODATA {
+            return Ok(Vec::new());
+        }
+        if ret == -libc::EFAULT {
+            bail!("container does not keep a console log");
+        }
+        if ret < 0 {
+            bail!("failed to retrieve console log");
+        }
+
+        if log.data.is_null() {
+            return Ok(Vec::new());
+        }
+        let data = unsafe {
+            let data =
+                std::slice::from_raw_parts(log.data as *const u8, len as usize)
+                    .to_vec();
+            libc::free(log.data as *mut _);
+            data
+        };
+        Ok(data)
+    }
+
     /// Freeze a running container.
     pub fn freeze(&self) -> Result<(), Error> {
         let frozen = unsafe { (*self.handle).freeze.unwrap()(self.handle) };
@@ -0,0 +1,223 @@
+// SPDX-License-Identifier: LGPL-2.1+
+
+//! Supervisor for syscalls a container's seccomp profile hands to userspace
+//! via the `notify` action.
+
+use anyhow::{bail, Error};
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::os::raw::{c_int, c_long};
+use std::os::unix::io::{AsRawFd, RawFd};
+
+/// The `AUDIT_ARCH_*` value of the architecture rlxc was built for, i.e. the
+/// one `libc::SYS_*` syscall numbers belong to. This is 0 on architectures
+/// unknown to rlxc, which matches no request.
+#[cfg(target_arch = "x86_64")]
+pub const AUDIT_ARCH_NATIVE: u32 = 0xc000_003e;
+#[cfg(target_arch = "x86")]
+pub const AUDIT_ARCH_NATIVE: u32 = 0x4000_0003;
+#[cfg(target_arch = "aarch64")]
+pub const AUDIT_ARCH_NATIVE: u32 = 0xc000_00b7;
+#[cfg(target_arch = "arm")]
+pub const AUDIT_ARCH_NATIVE: u32 = 0x4000_0028;
+#[cfg(all(target_arch = "powerpc64", target_endian = "little"))]
+pub const AUDIT_ARCH_NATIVE: u32 = 0xc000_0015;
+#[cfg(target_arch = "s390x")]
+pub const AUDIT_ARCH_NATIVE: u32 = 0x8000_0016;
+#[cfg(target_arch = "riscv64")]
+pub const AUDIT_ARCH_NATIVE: u32 = 0xc000_00f3;
+#[cfg(not(any(
+    target_arch = "x86_64",
+    target_arch = "x86",
+    target_arch = "aarch64",
+    target_arch = "arm",
+    all(target_arch = "powerpc64", target_endian = "little"),
+    target_arch = "s390x",
+    target_arch = "riscv64"
+)))]
+pub const AUDIT_ARCH_NATIVE: u32 = 0;
+
+/// How to answer an intercepted syscall.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeccompResponse {
+    /// Let the kernel execute the syscall as if it had not been intercepted.
+    Continue,
+    /// Fail the syscall with the given errno.
+    Errno(c_int),
+    /// Skip the syscall and return the given value, i.e. it was emulated.
+    Value(i64),
+}
+
+/// A syscall intercepted in a container.
+pub struct SeccompRequest<'a> {
+    fd: RawFd,
+    notif: &'a libc::seccomp_notif,
+}
+
+impl<'a> SeccompRequest<'a> {
+    /// The `AUDIT_ARCH_*` value of the calling task's syscall ABI. This
+    /// differs from `AUDIT_ARCH_NATIVE` for e.g. 32-bit tasks on a 64-bit
+    /// host, which use different syscall numbers and argument layouts.
+    pub fn arch(&self) -> u32 {
+        self.notif.data.arch
+    }
+
+    /// The syscall number, which depends on `arch`.
+    pub fn syscall(&self) -> c_long {
+        self.notif.data.nr as c_long
+    }
+
+    /// The syscall arguments.
+    pub fn args(&self) -> &[u64; 6] {
+        &self.notif.data.args
+    }
+
+    /// The pid of the calling thread, as seen from the supervisor.
+    pub fn pid(&self) -> u32 {
+        self.notif.pid
+    }
+
+    /// Check that the calling thread is still waiting for this request. The
+    /// pid may have been recycled otherwise, so this needs to be checked
+    /// after opening anything below `/proc/<pid>` and before trusting it.
+    pub fn is_valid(&self) -> bool {
+        unsafe {
+            libc::ioctl(
+                self.fd,
+                libc::SECCOMP_IOCTL_NOTIF_ID_VALID,
+                &self.notif.id,
+            ) == 0
+        }
+    }
+
+    /// Read a nul-terminated string of at most `max` bytes from the memory
+    /// of the calling process.
+    pub fn read_string(&self, addr: u64, max: usize) -> Result<CString, Error> {
+        let mut mem = File::open(format!("/proc/{}/mem", self.notif.pid))?;
+        if !self.is_valid() {
+            bail!("seccomp notification is no longer valid");
+        }
+
+        mem.seek(SeekFrom::Start(addr))?;
+        let mut data = Vec::new();
+        mem.take(max as u64).read_to_end(&mut data)?;
+        match data.iter().position(|&c| c == 0) {
+            Some(len) => {
+                data.truncate(len);
+                Ok(CString::new(data)?)
+            }
+            None => bail!("string at {:#x} exceeds {} bytes", addr, max),
+        }
+    }
+}
+
+type Handler = Box<dyn FnMut(&SeccompRequest) -> SeccompResponse>;
+
+/// Receives seccomp notifications of a container (see
+/// `Lxc::seccomp_notify_fd_active`) and answers them using the handler
+/// registered for the architecture and syscall. Syscalls without a handler,
+/// including all syscalls of architectures without handlers, continue.
+pub struct SeccompSupervisor {
+    fd: File,
+    handlers: HashMap<(u32, c_long), Handler>,
+}
+
+impl SeccompSupervisor {
+    pub fn new(fd: File) -> Self {
+        Self {
+            fd,
+            handlers: HashMap::new(),
+        }
+    }
+
+    /// Handle the syscall with number `syscall` of the architecture `arch`,
+    /// e.g. `libc::SYS_mknodat` of `AUDIT_ARCH_NATIVE`.
+    pub fn handle<F>(mut self, arch: u32, syscall: c_long, handler: F) -> Self
+    where
+        F: FnMut(&SeccompRequest) -> SeccompResponse + 'static,
+    {
+        self.handlers.insert((arch, syscall), Box::new(handler));
+        self
+    }
+
+    /// Wait for the next notification and answer it.
+    pub fn handle_next(&mut self) -> Result<(), Error> {
+        let fd = self.fd.as_raw_fd();
+        let mut notif: libc::seccomp_notif = unsafe { std::mem::zeroed() };
+        loop {
+            if unsafe {
+                libc::ioctl(fd, libc::SECCOMP_IOCTL_NOTIF_RECV, &mut notif)
+            } == 0
+            {
+                break;
+            }
+            let err = io::Error::last_os_error();
+            // The calling thread was interrupted or exited meanwhile.
+            if err.raw_os_error() == Some(libc::ENOENT) {
+                return Ok(());
+            }
+            if err.kind() != io::ErrorKind::Interrupted {
+                bail!("failed to receive seccomp notification: {}", err);
+            }
+            notif = unsafe { std::mem::zeroed() };
+        }
+
+        let request = SeccompRequest { fd, notif: &notif };
+        let key = (request.arch(), request.syscall());
+        let response = match self.handlers.get_mut(&key) {
+            Some(handler) => handler(&request),
+            None => SeccompResponse::Continue,
+        };
+
+        let mut resp = libc::seccomp_notif_resp {
+            id: notif.id,
+            val: 0,
+            error: 0,
+            flags: 0,
+        };
+        match response {
+            SeccompResponse::Continue => {
+                resp.flags = libc::SECCOMP_USER_NOTIF_FLAG_CONTINUE as u32
+            }
+            SeccompResponse::Errno(errno) => resp.error = -errno,
+            SeccompResponse::Value(val) => resp.val = val,
+        }
+
+        if unsafe { libc::ioctl(fd, libc::SECCOMP_IOCTL_NOTIF_SEND, &resp) } < 0
+        {
+            let err = io::Error::last_os_error();
+            // The calling thread was interrupted or exited meanwhile.
+            if err.raw_os_error() != Some(libc::ENOENT) {
+                bail!("failed to answer seccomp notification: {}", err);
+            }
+        }
+        Ok(())
+    }
+
+    /// Answer notifications until the container stops.
+    pub fn run(&mut self) -> Result<(), Error> {
+        let mut pfd = libc::pollfd {
+            fd: self.fd.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        loop {
+            if unsafe { libc::poll(&mut pfd, 1, -1) } < 0 {
+                let err = io::Error::last_os_error();
+                if err.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                bail!("failed to poll seccomp notifications: {}", err);
+            }
+            // All tasks using the filter are gone.
+            if pfd.revents & libc::POLLHUP != 0 {
+                return Ok(());
+            }
+            if pfd.revents & libc::POLLIN != 0 {
+                self.handle_next()?;
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+// SPDX-License-Identifier: LGPL-2.1+
+
+use std::ffi::{CStr, OsStr};
+use std::os::raw::c_char;
+use std::os::unix::ffi::OsStrExt;
+use std::path::PathBuf;
+
+/// A snapshot of a container. This is an owned copy of liblxc's `struct
+/// lxc_snapshot`.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    /// Name of the snapshot, e.g. `snap0`.
+    pub name: String,
+    /// Time the snapshot was taken at.
+    pub timestamp: String,
+    /// Path to the file holding the snapshot's comment.
+    pub comment_path: Option<PathBuf>,
+    /// Path the snapshot resides under.
+    pub lxcpath: PathBuf,
+}
+
+unsafe fn to_string(ptr: *const c_char) -> String {
+    if ptr.is_null() {
+        return String::new();
+    }
+    CStr::from_ptr(ptr).to_string_lossy().into_owned()
+}
+
+unsafe fn to_path(ptr: *const c_char) -> Option<PathBuf> {
+    if ptr.is_null() {
+        return None;
+    }
+    Some(PathBuf::from(OsStr::from_bytes(
+        CStr::from_ptr(ptr).to_bytes(),
+    )))
+}
+
+impl Snapshot {
+    /// Copy the contents of a `struct lxc_snapshot`.
+    ///
+    /// # Safety
+    ///
+    /// `raw` must have been filled in by liblxc's `snapshot_list`. The caller
+    /// is still responsible for freeing it.
+    pub(crate) unsafe fn from_raw(raw: &lxc_sys::lxc_snapshot) -> Self {
+        Self {
+            name: to_string(raw.name),
+            timestamp: to_string(raw.timestamp),
+            comment_path: to_path(raw.comment_pathname),
+            lxcpath: to_path(raw.lxcpath).unwrap_or_default(),
+        }
+    }
+
+    /// Read the snapshot's comment if it has one.
+    pub fn comment(&self) -> Option<String> {
+        let path = self.comment_path.as_ref()?;
+        std::fs::read_to_string(path)
+            .ok()
+            .map(|s| s.trim_end().to_string())
+    }
+}
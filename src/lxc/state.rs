@@ -0,0 +1,88 @@
+// SPDX-License-Identifier: LGPL-2.1+
+
+use anyhow::{bail, Error};
+use std::fmt;
+use std::str::FromStr;
+
+/// The state of a container as reported by liblxc.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Stopped,
+    Starting,
+    Running,
+    Stopping,
+    Aborting,
+    Freezing,
+    Frozen,
+    Thawed,
+    Unknown,
+}
+
+impl State {
+    /// The name liblxc uses for the state.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            State::Stopped => "STOPPED",
+            State::Starting => "STARTING",
+            State::Running => "RUNNING",
+            State::Stopping => "STOPPING",
+            State::Aborting => "ABORTING",
+            State::Freezing => "FREEZING",
+            State::Frozen => "FROZEN",
+            State::Thawed => "THAWED",
+            State::Unknown => "UNKNOWN",
+        }
+    }
+}
+
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for State {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_ascii_uppercase().as_str() {
+            "STOPPED" => State::Stopped,
+            "STARTING" => State::Starting,
+            "RUNNING" => State::Running,
+            "STOPPING" => State::Stopping,
+            "ABORTING" => State::Aborting,
+            "FREEZING" => State::Freezing,
+            "FROZEN" => State::Frozen,
+            "THAWED" => State::Thawed,
+            "UNKNOWN" => State::Unknown,
+            _ => bail!("invalid container state {}", s),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state() {
+        let states = [
+            State::Stopped,
+            State::Starting,
+            State::Running,
+            State::Stopping,
+            State::Aborting,
+            State::Freezing,
+            State::Frozen,
+            State::Thawed,
+            State::Unknown,
+        ];
+        for state in &states {
+            assert_eq!(state.to_string().parse::<State>().unwrap(), *state);
+        }
+        assert_eq!(State::Running.to_string(), "RUNNING");
+        assert_eq!("frozen".parse::<State>().unwrap(), State::Frozen);
+        assert!("".parse::<State>().is_err());
+        assert!("RUN".parse::<State>().is_err());
+    }
+}
@@ -0,0 +1,84 @@
+// SPDX-License-Identifier: LGPL-2.1+
+
+use std::os::raw::c_int;
+use std::os::unix::io::AsRawFd;
+
+/// Type representing options for how to attach to a container's terminal.
+pub struct TerminalOptions<'a> {
+    ttynum: c_int,
+    escape: c_int,
+    stdin: Option<&'a dyn AsRawFd>,
+    stdout: Option<&'a dyn AsRawFd>,
+    stderr: Option<&'a dyn AsRawFd>,
+}
+
+impl TerminalOptions<'static> {
+    pub fn new() -> Self {
+        Self {
+            ttynum: 0,
+            escape: 1,
+            stdin: None,
+            stdout: None,
+            stderr: None,
+        }
+    }
+}
+
+impl Default for TerminalOptions<'static> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> TerminalOptions<'a> {
+    /// The tty `Lxc::terminal` attaches to. `0` is the container's console
+    /// (the default), `-1` selects the first unused tty. `Console::proxy`
+    /// always uses the tty it was allocated for.
+    pub fn ttynum(mut self, v: c_int) -> Self {
+        self.ttynum = v;
+        self
+    }
+
+    /// The escape character as a control character, e.g. `1` for `Ctrl-a`
+    /// (the default). `<escape> q` detaches from the terminal. `-1` disables
+    /// the escape character of `Console::proxy` and `proxy_terminal`.
+    pub fn escape(mut self, v: c_int) -> Self {
+        self.escape = v;
+        self
+    }
+
+    pub fn stdin<T: AsRawFd>(mut self, file: &'a T) -> Self {
+        self.stdin = Some(file);
+        self
+    }
+
+    pub fn stdout<T: AsRawFd>(mut self, file: &'a T) -> Self {
+        self.stdout = Some(file);
+        self
+    }
+
+    pub fn stderr<T: AsRawFd>(mut self, file: &'a T) -> Self {
+        self.stderr = Some(file);
+        self
+    }
+
+    pub(crate) fn get_ttynum(&self) -> c_int {
+        self.ttynum
+    }
+
+    pub(crate) fn get_escape(&self) -> c_int {
+        self.escape
+    }
+
+    pub(crate) fn stdin_fd(&self) -> c_int {
+        self.stdin.map(|f| f.as_raw_fd()).unwrap_or(0)
+    }
+
+    pub(crate) fn stdout_fd(&self) -> c_int {
+        self.stdout.map(|f| f.as_raw_fd()).unwrap_or(1)
+    }
+
+    pub(crate) fn stderr_fd(&self) -> c_int {
+        self.stderr.map(|f| f.as_raw_fd()).unwrap_or(2)
+    }
+}
@@ -4,7 +4,7 @@
 
 use std::borrow::Cow;
 use std::ffi::{CStr, CString, NulError};
-use std::os::raw::c_char;
+use std::os::raw::{c_char, c_int};
 use std::os::unix::ffi::OsStrExt;
 
 /// Helper to create a C string array (`char**`) variable with the ownership
@@ -125,6 +125,39 @@ impl Drop for StringArrayIter {
     }
 }
 
+/// Helper for liblxc functions which copy a string into a caller provided
+/// buffer of length `inlen` and return the length of the full string. The
+/// function is called once to query the length and again to retrieve the
+/// string, retrying with a larger buffer if the string grew in between.
+/// Returns `None` if liblxc reports an error or the string keeps growing.
+pub fn read_sized_string<F>(f: F) -> Option<String>
+where
+    F: Fn(*mut c_char, c_int) -> c_int,
+{
+    const ATTEMPTS: usize = 3;
+
+    let mut len = f(std::ptr::null_mut(), 0);
+    for _ in 0..ATTEMPTS {
+        if len < 0 {
+            return None;
+        }
+
+        let mut buf = vec![0u8; len as usize + 1];
+        let full_len = f(buf.as_mut_ptr() as *mut c_char, buf.len() as c_int);
+        if full_len < 0 {
+            return None;
+        }
+
+        // The last byte is taken by the terminating nul.
+        if (full_len as usize) < buf.len() {
+            buf.truncate(full_len as usize);
+            return Some(String::from_utf8_lossy(&buf).into_owned());
+        }
+        len = full_len;
+    }
+    None
+}
+
 /// Helper trait allowing faster conversion from various string types to
 /// `CStrings`.
 ///
@@ -3,3 +3,4 @@
 //! Collection of various utilties.
 
 pub mod ffi;
+pub mod tty;
@@ -0,0 +1,89 @@
+// SPDX-License-Identifier: LGPL-2.1+
+
+//! Helpers to deal with terminals.
+
+use std::fs::File;
+use std::io;
+use std::os::raw::c_int;
+use std::os::unix::io::{FromRawFd, RawFd};
+
+/// Determine if `fd` refers to a terminal.
+pub fn is_tty(fd: RawFd) -> bool {
+    unsafe { libc::isatty(fd) == 1 }
+}
+
+/// Copy the window size of the terminal `from` to the terminal `to`.
+pub fn copy_winsize(from: RawFd, to: RawFd) -> io::Result<()> {
+    let mut ws: libc::winsize = unsafe { std::mem::zeroed() };
+    if unsafe { libc::ioctl(from, libc::TIOCGWINSZ, &mut ws) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    if unsafe { libc::ioctl(to, libc::TIOCSWINSZ, &ws) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Puts a terminal into raw mode. The previous settings are restored on drop.
+pub struct RawMode {
+    fd: RawFd,
+    orig: libc::termios,
+}
+
+impl RawMode {
+    pub fn new(fd: RawFd) -> io::Result<Self> {
+        let mut orig: libc::termios = unsafe { std::mem::zeroed() };
+        if unsafe { libc::tcgetattr(fd, &mut orig) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut raw = orig;
+        unsafe { libc::cfmakeraw(&mut raw) };
+        if unsafe { libc::tcsetattr(fd, libc::TCSAFLUSH, &raw) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self { fd, orig })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        unsafe { libc::tcsetattr(self.fd, libc::TCSAFLUSH, &self.orig) };
+    }
+}
+
+/// Open a new pty from the devpts instance `devpts` refers to. Returns the
+/// master and the slave side.
+pub fn open_pty(devpts: RawFd) -> io::Result<(File, File)> {
+    let fd = unsafe {
+        libc::openat(
+            devpts,
+            b"ptmx\0".as_ptr() as *const _,
+            libc::O_RDWR | libc::O_NOCTTY | libc::O_CLOEXEC,
+        )
+    };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let ptx = unsafe { File::from_raw_fd(fd) };
+
+    let unlock: c_int = 0;
+    if unsafe { libc::ioctl(fd, libc::TIOCSPTLCK, &unlock) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // Opening the peer through the master avoids looking the slave up by
+    // path, which would resolve in our own /dev/pts.
+    let fd = unsafe {
+        libc::ioctl(
+            fd,
+            libc::TIOCGPTPEER,
+            libc::O_RDWR | libc::O_NOCTTY | libc::O_CLOEXEC,
+        )
+    };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let pty = unsafe { File::from_raw_fd(fd) };
+    Ok((ptx, pty))
+}